@@ -1,5 +1,5 @@
 struct VertexInput {
-    @location(0) position: vec3<f32>,
+    @location(0) position: vec3<{{ position_type }}>,
 
 {% if has_normal %}
     @location(1) normal: vec3<f32>,
@@ -10,16 +10,21 @@ struct VertexInput {
 
 {% endif %}
 
-{% if has_tex_coord_0 %}
-    @location({{ tex_coord_0_location }}) tex_coord_0: vec2<f32>,
-{% endif %}
+{% for tex_coord in tex_coords %}
+    @location({{ tex_coord.location }}) tex_coord_{{ loop.index0 }}: vec2<{{ tex_coord.type }}>,
+{% endfor %}
 
-{% if has_tex_coord_1 %}
-    @location({{ tex_coord_1_location }}) tex_coord_1: vec2<f32>,
+{% for color in colors %}
+{% if color.has_alpha %}
+    @location({{ color.location }}) color_{{ loop.index0 }}: vec4<f32>,
+{% else %}
+    @location({{ color.location }}) color_{{ loop.index0 }}: vec3<f32>,
 {% endif %}
+{% endfor %}
 
-{% if has_color_0 %}
-    @location({{ color_0_location }}) color_0: vec4<f32>,
+{% if skin %}
+    @location({{ skin.joints_location }}) joints: vec4<u32>,
+    @location({{ skin.weights_location }}) weights: vec4<f32>,
 {% endif %}
 }
 
@@ -36,15 +41,11 @@ struct VertexOutput {
 
 {% endif %}
 
-{% if has_tex_coord_0 %}
-    @location({{ tex_coord_0_location }}) tex_coord_0: vec2<f32>,
-{% endif %}
+{% for tex_coord in tex_coords %}
+    @location({{ tex_coord.location }}) tex_coord_{{ loop.index0 }}: vec2<f32>,
+{% endfor %}
 
-{% if has_tex_coord_1 %}
-    @location({{ tex_coord_1_location }}) tex_coord_1: vec2<f32>,
-{% endif %}
-
-{% if has_color_0 %}
-    @location({{ color_0_location }}) color_0: vec4<f32>,
-{% endif %}
+{% for color in colors %}
+    @location({{ color.location }}) color_{{ loop.index0 }}: vec4<f32>,
+{% endfor %}
 }
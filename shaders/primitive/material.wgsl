@@ -2,6 +2,24 @@ struct MetallicRoughnessUniform {
     base_color_factor: vec4<f32>,
     metallic_factor: f32,
     roughness_factor: f32,
+    normal_scale: f32,
+    occlusion_strength: f32,
+    emissive_factor: vec3<f32>,
+    emissive_strength: f32,
+    alpha_cutoff: f32,
+    base_color_texture_transform: mat3x3<f32>,
+    metallic_roughness_texture_transform: mat3x3<f32>,
+    normal_texture_transform: mat3x3<f32>,
+    occlusion_texture_transform: mat3x3<f32>,
+    emissive_texture_transform: mat3x3<f32>,
+}
+
+// Applies the KHR_texture_transform matrix of a texture to its texture coordinates
+fn transform_tex_coord(
+    texture_transform: mat3x3<f32>,
+    tex_coord: vec2<f32>,
+) -> vec2<f32> {
+    return (texture_transform * vec3<f32>(tex_coord, 1.0)).xy;
 }
 
 @group(2) @binding(0)
@@ -16,3 +34,18 @@ var base_color_sampler: sampler;
 var metallic_roughness_texture: texture_2d<f32>;
 @group(2) @binding(4)
 var metallic_roughness_sampler: sampler;
+
+@group(2) @binding(5)
+var normal_texture: texture_2d<f32>;
+@group(2) @binding(6)
+var normal_sampler: sampler;
+
+@group(2) @binding(7)
+var occlusion_texture: texture_2d<f32>;
+@group(2) @binding(8)
+var occlusion_sampler: sampler;
+
+@group(2) @binding(9)
+var emissive_texture: texture_2d<f32>;
+@group(2) @binding(10)
+var emissive_sampler: sampler;
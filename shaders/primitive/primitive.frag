@@ -5,25 +5,93 @@
 {% include "primitive/pbr.wgsl" %}
 
 @fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let albedo = (
-        metallic_roughness.base_color_factor * textureSample(
-            base_color_texture,
-            base_color_sampler,
-            in.tex_coord_0,
-        )
+fn fs_main(
+    in: VertexOutput,
+    @builtin(front_facing) front_facing: bool,
+) -> @location(0) vec4<f32> {
+    // Textures that reference a texture coordinate set missing from the primitive are sampled at the origin
+{% for index in range(end=material_tex_coord_set_count) %}
+{% if index < tex_coords | length %}
+    let tex_coord_{{ index }} = in.tex_coord_{{ index }};
+{% else %}
+    let tex_coord_{{ index }} = vec2<f32>(0.0);
+{% endif %}
+{% endfor %}
+
+    let base_color_tex_coord = transform_tex_coord(
+        metallic_roughness.base_color_texture_transform,
+        tex_coord_{{ tex_coord_sets.base_color }},
+    );
+    let metallic_roughness_tex_coord = transform_tex_coord(
+        metallic_roughness.metallic_roughness_texture_transform,
+        tex_coord_{{ tex_coord_sets.metallic_roughness }},
+    );
+    let normal_tex_coord = transform_tex_coord(
+        metallic_roughness.normal_texture_transform,
+        tex_coord_{{ tex_coord_sets.normal }},
+    );
+    let occlusion_tex_coord = transform_tex_coord(
+        metallic_roughness.occlusion_texture_transform,
+        tex_coord_{{ tex_coord_sets.occlusion }},
+    );
+    let emissive_tex_coord = transform_tex_coord(
+        metallic_roughness.emissive_texture_transform,
+        tex_coord_{{ tex_coord_sets.emissive }},
+    );
+
+    var base_color = metallic_roughness.base_color_factor * textureSample(
+        base_color_texture,
+        base_color_sampler,
+        base_color_tex_coord,
+    );
+{% if colors | length > 0 %}
+    base_color *= in.color_0;
+{% endif %}
+    let albedo = base_color.rgb;
+
+    let emissive = metallic_roughness.emissive_factor * metallic_roughness.emissive_strength * textureSample(
+        emissive_texture,
+        emissive_sampler,
+        emissive_tex_coord,
     ).rgb;
 
+{% if unlit %}
+    let color = albedo + emissive;
+{% else %}
     let metallic_roughness_texture = textureSample(
         metallic_roughness_texture,
         metallic_roughness_sampler,
-        in.tex_coord_0,
+        metallic_roughness_tex_coord,
     );
 
     let roughness = metallic_roughness.roughness_factor * metallic_roughness_texture[1];
     let metallic = metallic_roughness.metallic_factor * metallic_roughness_texture[2];
 
-    let N = normalize(in.normal);
+    let vertex_normal = normalize(in.normal);
+
+{% if has_tangent and has_normal_texture %}
+    // Orthogonalize the interpolated tangent before building the tangent space basis
+    let tangent = normalize(in.tangent.xyz - dot(in.tangent.xyz, vertex_normal) * vertex_normal);
+    let bitangent = cross(vertex_normal, tangent) * in.tangent.w;
+    let TBN = mat3x3<f32>(tangent, bitangent, vertex_normal);
+
+    let tangent_space_normal = textureSample(
+        normal_texture,
+        normal_sampler,
+        normal_tex_coord,
+    ).xyz * 2.0 - 1.0;
+
+    let surface_normal = normalize(
+        TBN * vec3<f32>(
+            tangent_space_normal.xy * metallic_roughness.normal_scale,
+            tangent_space_normal.z,
+        )
+    );
+{% else %}
+    let surface_normal = vertex_normal;
+{% endif %}
+    // Back faces of double-sided materials are shaded with the normal facing the viewer
+    let N = select(-surface_normal, surface_normal, front_facing);
     let V = normalize(camera.position - in.world_position);
     let R = reflect(-V, N);
 
@@ -72,13 +140,34 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     ).rg;
     let specular = prefiltered_color * (F * brdf.x + brdf.y);
 
-    let ambient = kD * diffuse + specular;
+    let occlusion = 1.0 + metallic_roughness.occlusion_strength * (
+        textureSample(
+            occlusion_texture,
+            occlusion_sampler,
+            occlusion_tex_coord,
+        ).r - 1.0
+    );
+
+    let ambient = (kD * diffuse + specular) * occlusion;
 
     // Only ambient light is present within the scene
-    var color = ambient;
+    var color = ambient + emissive;
 
     // HDR tonemapping
     color = color / (color + vec3<f32>(1.0));
+{% endif %}
 
+{% if alpha_mode == "mask" %}
+    // Fragments are only discarded after all texture samples to keep the derivatives well-defined
+    if base_color.a < metallic_roughness.alpha_cutoff {
+        discard;
+    }
+
+    return vec4<f32>(color, 1.0);
+{% elif alpha_mode == "blend" %}
+    // The blend state of the render pipeline expects premultiplied alpha
+    return vec4<f32>(color * base_color.a, base_color.a);
+{% else %}
     return vec4<f32>(color, 1.0);
+{% endif %}
 }
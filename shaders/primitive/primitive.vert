@@ -2,38 +2,87 @@
 {% include "primitive/data.wgsl" %}
 {% include "primitive/material.wgsl" %}
 
+struct PrimitiveInstanceUniform {
+    transform: mat4x4<f32>,
+    normal_matrix: mat4x4<f32>,
+}
+
 @group(1) @binding(0)
-var<uniform> transform: mat4x4<f32>;
+var<uniform> primitive_instance: PrimitiveInstanceUniform;
+
+{% if skin %}
+// The length of the joint array has to match the maximum joint count of a skin
+@group(3) @binding(0)
+var<uniform> joints: array<mat4x4<f32>, 256>;
+
+// The cofactor matrix equals the inverse-transpose scaled by the determinant
+fn cofactor(m: mat3x3<f32>) -> mat3x3<f32> {
+    return mat3x3<f32>(
+        cross(m[1], m[2]),
+        cross(m[2], m[0]),
+        cross(m[0], m[1]),
+    );
+}
+{% endif %}
 
 @vertex
 fn vs_main(in: VertexInput) -> VertexOutput {
     var out: VertexOutput;
 
-    let world_position = transform * vec4<f32>(in.position, 1.0);
+{% if skin %}
+    let skin_matrix = in.weights.x * joints[in.joints.x]
+        + in.weights.y * joints[in.joints.y]
+        + in.weights.z * joints[in.joints.z]
+        + in.weights.w * joints[in.joints.w];
+    // The joint matrices are given in world space, so the transform of the mesh instance does not apply to skinned vertices
+    let transform = skin_matrix;
+
+    let transform_3x3 = mat3x3<f32>(transform[0].xyz, transform[1].xyz, transform[2].xyz);
+{% if double_sided %}
+    // Without culling, normals of mirrored faces flip along with their winding order, which front_facing undoes
+    let normal_matrix = cofactor(transform_3x3);
+{% else %}
+    // Normals are transformed with the inverse-transpose of the blended matrix, up to a positive scale
+    let normal_matrix = cofactor(transform_3x3) * sign(determinant(transform_3x3));
+{% endif %}
+{% else %}
+    let transform = primitive_instance.transform;
+    let normal_matrix = mat3x3<f32>(
+        primitive_instance.normal_matrix[0].xyz,
+        primitive_instance.normal_matrix[1].xyz,
+        primitive_instance.normal_matrix[2].xyz,
+    );
+{% endif %}
+
+    let world_position = transform * vec4<f32>(vec3<f32>(in.position), 1.0);
 
     out.clip_position = camera.view_projection * world_position;
     out.world_position = world_position.xyz;
 
 {% if has_normal %}
-    out.normal = (transform * vec4<f32>(in.normal, 1.0)).xyz;
+    out.normal = normal_matrix * in.normal;
 
 {% if has_tangent %}
-    out.tangent = transform * in.tangent;
+    // The tangent is a direction, while its w component holds the handedness of the bitangent
+    out.tangent = vec4<f32>(
+        (transform * vec4<f32>(in.tangent.xyz, 0.0)).xyz,
+        in.tangent.w,
+    );
 {% endif %}
 
 {% endif %}
 
-{% if has_tex_coord_0 %}
-    out.tex_coord_0 = in.tex_coord_0;
-{% endif %}
-
-{% if has_tex_coord_1 %}
-    out.tex_coord_1 = in.tex_coord_1;
-{% endif %}
+{% for tex_coord in tex_coords %}
+    out.tex_coord_{{ loop.index0 }} = vec2<f32>(in.tex_coord_{{ loop.index0 }});
+{% endfor %}
 
-{% if has_color_0 %}
-    out.color_0 = in.color_0;
+{% for color in colors %}
+{% if color.has_alpha %}
+    out.color_{{ loop.index0 }} = in.color_{{ loop.index0 }};
+{% else %}
+    out.color_{{ loop.index0 }} = vec4<f32>(in.color_{{ loop.index0 }}, 1.0);
 {% endif %}
+{% endfor %}
 
     return out;
 }
@@ -1,4 +1,3 @@
-use std::path::Path;
 use std::time::Instant;
 
 use winit::application::ApplicationHandler;
@@ -9,10 +8,7 @@ use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::{Window, WindowId};
 
 use crate::args::Args;
-use crate::resource::gltf::asset::file::FileSystemGltfAsset;
-use crate::resource::gltf::asset::GltfAsset;
-use crate::resource::gltf::loader::file::FileSystemGltfLoader;
-use crate::resource::ibl::file::FileSystemIblEnvironmentLoader;
+use crate::assets::load_assets;
 use crate::view::ViewSystem;
 
 pub struct App {
@@ -48,8 +44,12 @@ impl App {
         }
     }
 
-    async fn initialize_view_system(event_loop_proxy: EventLoopProxy<UserEvent>, window: Window) {
-        let view_system = ViewSystem::from_window(window).await.unwrap();
+    async fn initialize_view_system(
+        event_loop_proxy: EventLoopProxy<UserEvent>,
+        window: Window,
+        sample_count: u32,
+    ) {
+        let view_system = ViewSystem::from_window(window, sample_count).await.unwrap();
         assert!(event_loop_proxy
             .send_event(UserEvent::ViewSystemReady(view_system))
             .is_ok());
@@ -62,8 +62,9 @@ impl ApplicationHandler<UserEvent> for App {
 
         let window = App::create_window(event_loop);
         let event_loop_proxy = self.event_loop_proxy.clone();
+        let sample_count = self.args.sample_count;
         let future = async move {
-            App::initialize_view_system(event_loop_proxy, window).await;
+            App::initialize_view_system(event_loop_proxy, window, sample_count).await;
         };
 
         cfg_if::cfg_if! {
@@ -167,28 +168,7 @@ impl ApplicationHandler<UserEvent> for App {
 
         log::info!("View system created");
 
-        if let Some(ibl_environment) = &self.args.ibl_environment {
-            let ibl_environment_loader = FileSystemIblEnvironmentLoader {
-                paths: ibl_environment.clone(),
-            };
-
-            view_system
-                .render_system
-                .load_ibl_environment(&ibl_environment_loader)
-                .unwrap();
-        }
-
-        if let Some(gltf_file_path) = &self.args.gltf {
-            let asset = FileSystemGltfAsset::from_path(Path::new(gltf_file_path)).unwrap();
-            let default_scene = asset.gltf().default_scene().unwrap();
-
-            let mut gltf_loader = FileSystemGltfLoader::new(&asset);
-
-            view_system
-                .render_system
-                .load_scene(&asset, default_scene.index(), &mut gltf_loader)
-                .unwrap();
-        }
+        load_assets(&mut view_system.render_system, &self.args).unwrap();
 
         view_system.window.request_redraw();
         self.view_system = Some(view_system);
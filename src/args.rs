@@ -1,6 +1,7 @@
 pub struct Args {
     pub gltf: Option<String>,
     pub ibl_environment: Option<IblEnvironmentPaths>,
+    pub sample_count: u32,
 }
 
 #[derive(Clone)]
@@ -9,3 +10,13 @@ pub struct IblEnvironmentPaths {
     pub diffuse: String,
     pub specular: String,
 }
+
+pub struct HeadlessArgs {
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    pub camera_yaw: f32,
+    pub camera_pitch: f32,
+    pub camera_distance: f32,
+    pub force_fallback_adapter: bool,
+}
@@ -0,0 +1,40 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::args::Args;
+use crate::error::Error;
+use crate::render::RenderSystem;
+use crate::resource::gltf::asset::file::FileSystemGltfAsset;
+use crate::resource::gltf::asset::GltfAsset;
+use crate::resource::gltf::loader::file::FileSystemGltfLoader;
+use crate::resource::ibl::file::FileSystemIblEnvironmentLoader;
+
+pub fn load_assets(render_system: &mut RenderSystem, args: &Args) -> Result<()> {
+    if let Some(ibl_environment) = &args.ibl_environment {
+        let ibl_environment_loader = FileSystemIblEnvironmentLoader {
+            paths: ibl_environment.clone(),
+        };
+
+        render_system.load_ibl_environment(&ibl_environment_loader)?;
+    }
+
+    if let Some(gltf_file_path) = &args.gltf {
+        let asset = FileSystemGltfAsset::from_path(Path::new(gltf_file_path))?;
+        let default_scene = match asset.gltf().default_scene() {
+            Some(default_scene) => default_scene,
+            None => {
+                return Err(Error::new(format!(
+                    "The given glTF asset has no default scene: {gltf_file_path}"
+                ))
+                .into())
+            }
+        };
+
+        let mut gltf_loader = FileSystemGltfLoader::new(&asset);
+
+        render_system.load_scene(&asset, default_scene.index(), &mut gltf_loader)?;
+    }
+
+    Ok(())
+}
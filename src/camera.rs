@@ -29,7 +29,7 @@ impl OrbitalCameraController {
             target,
             distance,
             yaw,
-            pitch,
+            pitch: cgmath::Rad(pitch.0.clamp(-SAFE_FRAC_PI_2, SAFE_FRAC_PI_2)),
             sensitivity,
             is_left_mouse_pressed: false,
             rotation_horizontal: 0.0,
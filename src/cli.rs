@@ -1,12 +1,72 @@
-use clap::{Args, Parser};
+use clap::{Args, Parser, Subcommand};
 
 /// A basic viewer for the glTF 3D asset format
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
-    /// Path to the .gltf file of the asset that will be displayed by the viewer
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Display an asset in an interactive window
+    View(ViewCommand),
+
+    /// Render a still image of an asset to a .png file and exit
+    Render(RenderCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct ViewCommand {
+    /// Path to the .gltf or .glb file of the asset that will be displayed by the viewer
+    pub gltf: String,
+
+    /// Number of samples per pixel used for multisample anti-aliasing
+    #[arg(long, default_value_t = 4)]
+    pub sample_count: u32,
+
+    #[command(flatten)]
+    pub ibl_environment: Option<IblEnvironment>,
+}
+
+#[derive(Args, Debug)]
+pub struct RenderCommand {
+    /// Path to the .gltf or .glb file of the asset that will be rendered
     pub gltf: String,
 
+    /// Path to the .png file that the rendered image will be written to
+    #[arg(short = 'o', long)]
+    pub output: String,
+
+    /// Width of the rendered image in pixels
+    #[arg(long, default_value_t = 1920)]
+    pub width: u32,
+
+    /// Height of the rendered image in pixels
+    #[arg(long, default_value_t = 1080)]
+    pub height: u32,
+
+    /// Rotation of the camera around the vertical axis of the asset in degrees
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    pub yaw: f32,
+
+    /// Rotation of the camera above (positive) or below (negative) the asset in degrees
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    pub pitch: f32,
+
+    /// Distance between the camera and the center of the scene
+    #[arg(long, default_value_t = 10.0)]
+    pub distance: f32,
+
+    /// Only use a software adapter, e.g. on machines without a GPU
+    #[arg(long)]
+    pub force_fallback_adapter: bool,
+
+    /// Number of samples per pixel used for multisample anti-aliasing
+    #[arg(long, default_value_t = 4)]
+    pub sample_count: u32,
+
     #[command(flatten)]
     pub ibl_environment: Option<IblEnvironment>,
 }
@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::args::HeadlessArgs;
+use crate::camera::OrbitalCameraController;
+use crate::render::RenderSystem;
+
+pub struct HeadlessSystem {
+    pub render_system: RenderSystem,
+}
+
+impl HeadlessSystem {
+    pub async fn from_args(headless_args: &HeadlessArgs, sample_count: u32) -> Result<Self> {
+        let mut render_system = RenderSystem::from_dimensions(
+            winit::dpi::PhysicalSize::new(headless_args.width, headless_args.height),
+            headless_args.force_fallback_adapter,
+            sample_count,
+        )
+        .await?;
+
+        let camera_controller = OrbitalCameraController::new(
+            (0.0, 0.0, 0.0).into(),
+            headless_args.camera_distance,
+            cgmath::Deg(headless_args.camera_yaw).into(),
+            cgmath::Deg(headless_args.camera_pitch).into(),
+            0.0,
+        );
+
+        render_system.set_user_camera_transform(camera_controller.calculate_camera_transform());
+
+        Ok(Self { render_system })
+    }
+
+    pub fn render_to_file(&mut self, path: &Path) -> Result<()> {
+        let image = self.render_system.render_to_image()?;
+        image.save_with_format(path, image::ImageFormat::Png)?;
+
+        Ok(())
+    }
+}
@@ -1,11 +1,17 @@
+use std::path::Path;
+
 use crate::app::App;
-use crate::args::Args;
+use crate::args::{Args, HeadlessArgs};
+use crate::assets::load_assets;
+use crate::headless::HeadlessSystem;
 
 mod app;
 pub mod args;
+mod assets;
 mod camera;
 mod data;
 mod error;
+mod headless;
 mod render;
 mod resource;
 mod view;
@@ -27,3 +33,14 @@ pub fn run(args: Args) {
         }
     }
 }
+
+pub fn render(args: Args, headless_args: HeadlessArgs) -> anyhow::Result<()> {
+    env_logger::init();
+
+    let mut headless_system =
+        pollster::block_on(HeadlessSystem::from_args(&headless_args, args.sample_count))?;
+
+    load_assets(&mut headless_system.render_system, &args)?;
+
+    headless_system.render_to_file(Path::new(&headless_args.output))
+}
@@ -1,17 +1,42 @@
 use clap::Parser;
 
-use crate::cli::Cli;
-use gltf_viewer::args::Args;
-use gltf_viewer::run;
+use crate::cli::{Cli, Command};
+use gltf_viewer::args::{Args, HeadlessArgs};
+use gltf_viewer::{render, run};
 
 mod cli;
 
-fn main() {
+fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
-    run(Args {
-        gltf: Some(cli.gltf),
-        ibl_environment: cli
-            .ibl_environment
-            .map(|ibl_environment| ibl_environment.into()),
-    });
+    match cli.command {
+        Command::View(command) => {
+            run(Args {
+                gltf: Some(command.gltf),
+                ibl_environment: command
+                    .ibl_environment
+                    .map(|ibl_environment| ibl_environment.into()),
+                sample_count: command.sample_count,
+            });
+
+            Ok(())
+        }
+        Command::Render(command) => render(
+            Args {
+                gltf: Some(command.gltf),
+                ibl_environment: command
+                    .ibl_environment
+                    .map(|ibl_environment| ibl_environment.into()),
+                sample_count: command.sample_count,
+            },
+            HeadlessArgs {
+                output: command.output,
+                width: command.width,
+                height: command.height,
+                camera_yaw: command.yaw,
+                camera_pitch: command.pitch,
+                camera_distance: command.distance,
+                force_fallback_adapter: command.force_fallback_adapter,
+            },
+        ),
+    }
 }
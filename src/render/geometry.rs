@@ -0,0 +1,360 @@
+use anyhow::Result;
+use cgmath::InnerSpace;
+
+use crate::error::Error;
+
+pub struct PrimitiveGeometry {
+    indices: Vec<u32>,
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    tex_coords: Vec<[f32; 2]>,
+    tangents: Vec<[f32; 4]>,
+}
+
+impl PrimitiveGeometry {
+    pub fn new(
+        indices: Option<Vec<u32>>,
+        positions: Vec<[f32; 3]>,
+        normals: Vec<[f32; 3]>,
+        tex_coords: Vec<[f32; 2]>,
+    ) -> Result<Self> {
+        let vertex_count = positions.len();
+
+        if normals.len() != vertex_count || tex_coords.len() != vertex_count {
+            return Err(Error::new(String::from(
+                "The vertex attributes of the given primitive have different lengths.",
+            ))
+            .into());
+        }
+
+        let indices = match indices {
+            Some(indices) => indices,
+            None => (0..vertex_count as u32).collect(),
+        };
+
+        if indices.iter().any(|index| *index as usize >= vertex_count) {
+            return Err(Error::new(String::from(
+                "The given primitive has an index that is out of range.",
+            ))
+            .into());
+        }
+
+        let corner_count = indices.len();
+
+        Ok(Self {
+            indices,
+            positions,
+            normals,
+            tex_coords,
+            tangents: vec![[1.0, 0.0, 0.0, 1.0]; corner_count],
+        })
+    }
+
+    // The tangents are generated per face corner, since faces sharing a vertex may disagree on them
+    pub fn generate_tangents(mut self) -> Result<Vec<[f32; 4]>> {
+        if !bevy_mikktspace::generate_tangents(&mut self) {
+            return Err(Error::new(String::from(
+                "Unable to generate tangents for the given primitive.",
+            ))
+            .into());
+        }
+
+        Ok(self.tangents)
+    }
+
+    pub fn generate_flat_normals(positions: &[[f32; 3]]) -> Vec<[f32; 3]> {
+        let mut normals = Vec::<[f32; 3]>::with_capacity(positions.len());
+
+        for triangle in positions.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(cgmath::Vector3::from);
+            let normal = (b - a).cross(c - a);
+
+            // Degenerate triangles are not rasterized, so any unit vector will do for them
+            let normal = match normal.magnitude2() > 0.0 {
+                true => normal.normalize(),
+                false => cgmath::Vector3::unit_z(),
+            };
+
+            normals.extend_from_slice(&[normal.into(); 3]);
+        }
+
+        normals.resize(positions.len(), [0.0, 0.0, 1.0]);
+
+        normals
+    }
+
+    pub fn convert_line_loop(indices: &[u32]) -> Vec<u32> {
+        // The last vertex of a loop is connected back to the first one
+        indices
+            .iter()
+            .zip(indices.iter().cycle().skip(1))
+            .flat_map(|(a, b)| [*a, *b])
+            .collect()
+    }
+
+    pub fn convert_triangle_strip(indices: &[u32]) -> Vec<u32> {
+        // Every other triangle of a strip has its winding flipped to keep all faces consistent
+        indices
+            .windows(3)
+            .enumerate()
+            .flat_map(|(index, triangle)| match index % 2 {
+                0 => [triangle[0], triangle[1], triangle[2]],
+                _ => [triangle[0], triangle[2], triangle[1]],
+            })
+            .collect()
+    }
+
+    pub fn convert_triangle_fan(indices: &[u32]) -> Vec<u32> {
+        match indices.split_first() {
+            Some((center, rim)) => rim
+                .windows(2)
+                .flat_map(|edge| [edge[0], edge[1], *center])
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn deindex<E: Copy>(elements: &[E], indices: &[u32]) -> Result<Vec<E>> {
+        indices
+            .iter()
+            .map(|index| match elements.get(*index as usize) {
+                Some(element) => Ok(*element),
+                None => Err(Error::new(format!(
+                    "The given primitive has an index that is out of range: {index}"
+                ))
+                .into()),
+            })
+            .collect()
+    }
+
+    pub fn read_indices(data: &[u8], data_type: gltf::accessor::DataType) -> Result<Vec<u32>> {
+        match data_type {
+            gltf::accessor::DataType::U8 => Ok(data.iter().map(|index| *index as u32).collect()),
+            gltf::accessor::DataType::U16 => Ok(data
+                .chunks_exact(2)
+                .map(|index| u16::from_le_bytes([index[0], index[1]]) as u32)
+                .collect()),
+            gltf::accessor::DataType::U32 => Ok(data
+                .chunks_exact(4)
+                .map(|index| u32::from_le_bytes([index[0], index[1], index[2], index[3]]))
+                .collect()),
+            _ => Err(Error::new(format!(
+                "The given index data uses an invalid data type: {:?}",
+                data_type
+            ))
+            .into()),
+        }
+    }
+
+    pub fn read_elements<const N: usize>(
+        data: &[u8],
+        accessor: &gltf::Accessor,
+    ) -> Result<Vec<[f32; N]>> {
+        let data_type = accessor.data_type();
+        let component_size = data_type.size();
+
+        if accessor.dimensions().multiplicity() < N {
+            return Err(Error::new(format!(
+                "The given accessor has fewer than {N} components: {:?}",
+                accessor.dimensions()
+            ))
+            .into());
+        }
+
+        Ok(data
+            .chunks_exact(accessor.size())
+            .map(|element| {
+                std::array::from_fn(|index| {
+                    PrimitiveGeometry::read_component(
+                        &element[index * component_size..(index + 1) * component_size],
+                        data_type,
+                        accessor.normalized(),
+                    )
+                })
+            })
+            .collect())
+    }
+
+    fn read_component(data: &[u8], data_type: gltf::accessor::DataType, normalized: bool) -> f32 {
+        match (data_type, normalized) {
+            (gltf::accessor::DataType::I8, true) => (data[0] as i8 as f32 / 127.0).max(-1.0),
+            (gltf::accessor::DataType::I8, false) => data[0] as i8 as f32,
+            (gltf::accessor::DataType::U8, true) => data[0] as f32 / 255.0,
+            (gltf::accessor::DataType::U8, false) => data[0] as f32,
+            (gltf::accessor::DataType::I16, true) => {
+                (i16::from_le_bytes([data[0], data[1]]) as f32 / 32767.0).max(-1.0)
+            }
+            (gltf::accessor::DataType::I16, false) => i16::from_le_bytes([data[0], data[1]]) as f32,
+            (gltf::accessor::DataType::U16, true) => {
+                u16::from_le_bytes([data[0], data[1]]) as f32 / 65535.0
+            }
+            (gltf::accessor::DataType::U16, false) => u16::from_le_bytes([data[0], data[1]]) as f32,
+            (gltf::accessor::DataType::U32, _) => {
+                u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as f32
+            }
+            (gltf::accessor::DataType::F32, _) => {
+                f32::from_le_bytes([data[0], data[1], data[2], data[3]])
+            }
+        }
+    }
+
+    fn get_vertex_index(&self, face: usize, vert: usize) -> usize {
+        self.indices[face * 3 + vert] as usize
+    }
+}
+
+impl bevy_mikktspace::Geometry for PrimitiveGeometry {
+    fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.positions[self.get_vertex_index(face, vert)]
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.normals[self.get_vertex_index(face, vert)]
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.tex_coords[self.get_vertex_index(face, vert)]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.tangents[face * 3 + vert] = tangent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PrimitiveGeometry;
+
+    #[test]
+    fn read_indices_widens_u8_indices() {
+        assert_eq!(
+            PrimitiveGeometry::read_indices(&[0, 1, 255], gltf::accessor::DataType::U8).unwrap(),
+            vec![0, 1, 255],
+        );
+    }
+
+    #[test]
+    fn generate_flat_normals_uses_the_winding_of_each_triangle() {
+        let normals = PrimitiveGeometry::generate_flat_normals(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 2.0],
+            [2.0, 0.0, 0.0],
+        ]);
+
+        assert_eq!(
+            normals,
+            vec![
+                [0.0, 0.0, 1.0],
+                [0.0, 0.0, 1.0],
+                [0.0, 0.0, 1.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+            ],
+        );
+    }
+
+    #[test]
+    fn convert_topologies_to_lists() {
+        assert_eq!(
+            PrimitiveGeometry::convert_line_loop(&[0, 1, 2]),
+            vec![0, 1, 1, 2, 2, 0],
+        );
+        assert_eq!(
+            PrimitiveGeometry::convert_triangle_strip(&[0, 1, 2, 3]),
+            vec![0, 1, 2, 1, 3, 2],
+        );
+        assert_eq!(
+            PrimitiveGeometry::convert_triangle_fan(&[0, 1, 2, 3]),
+            vec![1, 2, 0, 2, 3, 0],
+        );
+    }
+
+    #[test]
+    fn deindex_rejects_out_of_range_indices() {
+        assert_eq!(
+            PrimitiveGeometry::deindex(&[1, 2, 3], &[2, 0, 0]).unwrap(),
+            vec![3, 1, 1]
+        );
+        assert!(PrimitiveGeometry::deindex(&[1, 2, 3], &[3]).is_err());
+    }
+
+    #[test]
+    fn read_component_decodes_normalized_integers() {
+        assert_eq!(
+            PrimitiveGeometry::read_component(&[127], gltf::accessor::DataType::I8, true),
+            1.0
+        );
+        assert_eq!(
+            PrimitiveGeometry::read_component(&[128], gltf::accessor::DataType::I8, true),
+            -1.0
+        );
+        assert_eq!(
+            PrimitiveGeometry::read_component(&[255, 255], gltf::accessor::DataType::U16, true),
+            1.0
+        );
+        assert_eq!(
+            PrimitiveGeometry::read_component(&[0, 128], gltf::accessor::DataType::I16, false),
+            -32768.0
+        );
+    }
+
+    #[test]
+    fn generate_tangents_follows_the_u_direction() {
+        let geometry = PrimitiveGeometry::new(
+            Some(vec![0, 1, 2, 0, 2, 3]),
+            vec![
+                [-1.0, -1.0, 0.0],
+                [1.0, -1.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [-1.0, 1.0, 0.0],
+            ],
+            vec![[0.0, 0.0, 1.0]; 4],
+            vec![[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]],
+        )
+        .unwrap();
+
+        for tangent in geometry.generate_tangents().unwrap() {
+            assert!((tangent[0] - 1.0).abs() < 1e-5);
+            assert!(tangent[1].abs() < 1e-5);
+            assert!(tangent[2].abs() < 1e-5);
+            assert!(tangent[3].abs() == 1.0);
+        }
+    }
+
+    #[test]
+    fn generate_tangents_splits_shared_vertices_with_different_handedness() {
+        // The texture coordinates of the second face are mirrored, which flips the handedness of its bitangent
+        let geometry = PrimitiveGeometry::new(
+            Some(vec![0, 1, 2, 0, 2, 3]),
+            vec![
+                [-1.0, -1.0, 0.0],
+                [1.0, -1.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [-1.0, 1.0, 0.0],
+            ],
+            vec![[0.0, 0.0, 1.0]; 4],
+            vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [2.0, 0.0]],
+        )
+        .unwrap();
+
+        let tangents = geometry.generate_tangents().unwrap();
+
+        assert_eq!(tangents.len(), 6);
+        assert_eq!(tangents[0][3], tangents[1][3]);
+        assert_eq!(tangents[3][3], tangents[5][3]);
+        assert_eq!(tangents[0][3], -tangents[3][3]);
+        assert_eq!(tangents[2][3], -tangents[4][3]);
+    }
+}
@@ -1,34 +1,43 @@
 use crate::render::texture::Texture2DPackage;
 
-pub struct Material {
-    base_color_factor: [f32; 4],
+pub struct MaterialParameters {
+    pub base_color_factor: [f32; 4],
     #[allow(dead_code)]
-    base_color_texture: std::rc::Rc<Texture2DPackage>,
-    metallic_factor: f32,
-    roughness_factor: f32,
+    pub base_color_texture: std::rc::Rc<Texture2DPackage>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    #[allow(dead_code)]
+    pub metallic_roughness_texture: std::rc::Rc<Texture2DPackage>,
+    #[allow(dead_code)]
+    pub normal_texture: std::rc::Rc<Texture2DPackage>,
+    pub normal_scale: f32,
     #[allow(dead_code)]
-    metallic_roughness_texture: std::rc::Rc<Texture2DPackage>,
+    pub occlusion_texture: std::rc::Rc<Texture2DPackage>,
+    pub occlusion_strength: f32,
+    pub emissive_factor: [f32; 3],
+    #[allow(dead_code)]
+    pub emissive_texture: std::rc::Rc<Texture2DPackage>,
+    pub emissive_strength: f32,
+    pub alpha_mode: AlphaMode,
+    pub alpha_cutoff: f32,
+    pub texture_transforms: MaterialTextureTransforms,
+}
+
+pub struct Material {
+    parameters: MaterialParameters,
     pub gpu_metallic_roughness_uniform_buffer: wgpu::Buffer,
     pub gpu_bind_group: wgpu::BindGroup,
 }
 
 impl Material {
     pub fn new(
-        base_color_factor: [f32; 4],
-        base_color_texture: std::rc::Rc<Texture2DPackage>,
-        metallic_factor: f32,
-        roughness_factor: f32,
-        metallic_roughness_texture: std::rc::Rc<Texture2DPackage>,
+        parameters: MaterialParameters,
         gpu_metallic_roughness_uniform_buffer: wgpu::Buffer,
         gpu_bind_group: wgpu::BindGroup,
         queue: &wgpu::Queue,
     ) -> Self {
         let object = Self {
-            base_color_factor,
-            base_color_texture,
-            metallic_factor,
-            roughness_factor,
-            metallic_roughness_texture,
+            parameters,
             gpu_metallic_roughness_uniform_buffer,
             gpu_bind_group,
         };
@@ -37,15 +46,27 @@ impl Material {
         object
     }
 
+    pub fn alpha_mode(&self) -> AlphaMode {
+        self.parameters.alpha_mode
+    }
+
+    pub fn tex_coord_sets(&self) -> MaterialTexCoordSets {
+        let texture_transforms = &self.parameters.texture_transforms;
+
+        MaterialTexCoordSets {
+            base_color: texture_transforms.base_color.tex_coord,
+            metallic_roughness: texture_transforms.metallic_roughness.tex_coord,
+            normal: texture_transforms.normal.tex_coord,
+            occlusion: texture_transforms.occlusion.tex_coord,
+            emissive: texture_transforms.emissive.tex_coord,
+        }
+    }
+
     fn initialize_uniform_buffer(&self, queue: &wgpu::Queue) {
         queue.write_buffer(
             &self.gpu_metallic_roughness_uniform_buffer,
             0,
-            bytemuck::cast_slice(&[MetallicRoughnessUniform::new(
-                self.base_color_factor,
-                self.metallic_factor,
-                self.roughness_factor,
-            )]),
+            bytemuck::cast_slice(&[MetallicRoughnessUniform::from_material(self)]),
         );
         queue.submit([]);
     }
@@ -57,16 +78,154 @@ pub struct MetallicRoughnessUniform {
     base_color_factor: [f32; 4],
     metallic_factor: f32,
     roughness_factor: f32,
-    _padding: u64,
+    normal_scale: f32,
+    occlusion_strength: f32,
+    emissive_factor: [f32; 3],
+    emissive_strength: f32,
+    alpha_cutoff: f32,
+    _padding_1: u32,
+    _padding_2: u32,
+    _padding_3: u32,
+    base_color_texture_transform: [[f32; 4]; 3],
+    metallic_roughness_texture_transform: [[f32; 4]; 3],
+    normal_texture_transform: [[f32; 4]; 3],
+    occlusion_texture_transform: [[f32; 4]; 3],
+    emissive_texture_transform: [[f32; 4]; 3],
 }
 
 impl MetallicRoughnessUniform {
-    pub fn new(base_color_factor: [f32; 4], metallic_factor: f32, roughness_factor: f32) -> Self {
+    pub fn from_material(material: &Material) -> Self {
+        let parameters = &material.parameters;
+        let texture_transforms = &parameters.texture_transforms;
+
+        Self {
+            base_color_factor: parameters.base_color_factor,
+            metallic_factor: parameters.metallic_factor,
+            roughness_factor: parameters.roughness_factor,
+            normal_scale: parameters.normal_scale,
+            occlusion_strength: parameters.occlusion_strength,
+            emissive_factor: parameters.emissive_factor,
+            emissive_strength: parameters.emissive_strength,
+            alpha_cutoff: parameters.alpha_cutoff,
+            _padding_1: 0,
+            _padding_2: 0,
+            _padding_3: 0,
+            base_color_texture_transform: texture_transforms.base_color.to_uniform_matrix(),
+            metallic_roughness_texture_transform: texture_transforms
+                .metallic_roughness
+                .to_uniform_matrix(),
+            normal_texture_transform: texture_transforms.normal.to_uniform_matrix(),
+            occlusion_texture_transform: texture_transforms.occlusion.to_uniform_matrix(),
+            emissive_texture_transform: texture_transforms.emissive.to_uniform_matrix(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MaterialTextureTransforms {
+    pub base_color: TextureTransform,
+    pub metallic_roughness: TextureTransform,
+    pub normal: TextureTransform,
+    pub occlusion: TextureTransform,
+    pub emissive: TextureTransform,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureTransform {
+    pub offset: [f32; 2],
+    pub rotation: f32,
+    pub scale: [f32; 2],
+    pub tex_coord: u32,
+}
+
+impl Default for TextureTransform {
+    fn default() -> Self {
         Self {
-            base_color_factor,
-            metallic_factor,
-            roughness_factor,
-            _padding: 0,
+            offset: [0.0, 0.0],
+            rotation: 0.0,
+            scale: [1.0, 1.0],
+            tex_coord: 0,
+        }
+    }
+}
+
+impl TextureTransform {
+    pub fn to_matrix(self) -> cgmath::Matrix3<f32> {
+        let (sin, cos) = self.rotation.sin_cos();
+
+        let translation_matrix = cgmath::Matrix3::new(
+            1.0,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+            self.offset[0],
+            self.offset[1],
+            1.0,
+        );
+        let rotation_matrix = cgmath::Matrix3::new(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0);
+        let scale_matrix = cgmath::Matrix3::new(
+            self.scale[0],
+            0.0,
+            0.0,
+            0.0,
+            self.scale[1],
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        );
+
+        translation_matrix * rotation_matrix * scale_matrix
+    }
+
+    fn to_uniform_matrix(self) -> [[f32; 4]; 3] {
+        // The columns of a mat3x3 are aligned to 16 bytes within uniform buffers
+        let matrix: [[f32; 3]; 3] = self.to_matrix().into();
+        matrix.map(|column| [column[0], column[1], column[2], 0.0])
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize)]
+pub struct MaterialTexCoordSets {
+    pub base_color: u32,
+    pub metallic_roughness: u32,
+    pub normal: u32,
+    pub occlusion: u32,
+    pub emissive: u32,
+}
+
+impl MaterialTexCoordSets {
+    pub fn max(&self) -> u32 {
+        [
+            self.base_color,
+            self.metallic_roughness,
+            self.normal,
+            self.occlusion,
+            self.emissive,
+        ]
+        .into_iter()
+        .fold(0, u32::max)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize)]
+pub enum AlphaMode {
+    #[serde(rename = "opaque")]
+    Opaque,
+    #[serde(rename = "mask")]
+    Mask,
+    #[serde(rename = "blend")]
+    Blend,
+}
+
+impl AlphaMode {
+    pub fn from_gltf(alpha_mode: gltf::material::AlphaMode) -> Self {
+        match alpha_mode {
+            gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => AlphaMode::Mask,
+            gltf::material::AlphaMode::Blend => AlphaMode::Blend,
         }
     }
 }
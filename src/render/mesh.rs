@@ -1,14 +1,26 @@
+use cgmath::{Matrix, SquareMatrix};
+
+use crate::render::pipeline::RenderPipeline;
 use crate::render::primitive::Primitive;
+use crate::render::skin::Skin;
 
 pub struct Mesh {
     pub primitives: Vec<std::rc::Rc<Primitive>>,
 }
 
+pub struct MeshInstancePrimitive {
+    pub render_pipeline: std::rc::Rc<RenderPipeline>,
+    // The centroid is given in world space, since skinned primitives are posed by their joints instead of the mesh instance
+    pub centroid: cgmath::Point3<f32>,
+}
+
 pub struct MeshInstance {
     pub mesh: std::rc::Rc<Mesh>,
+    pub primitives: Vec<MeshInstancePrimitive>,
     #[allow(dead_code)]
     pub gpu_transform_uniform_buffer: wgpu::Buffer,
     pub gpu_transform_bind_group: wgpu::BindGroup,
+    pub skin: Option<std::rc::Rc<Skin>>,
 }
 
 impl MeshInstance {
@@ -17,22 +29,21 @@ impl MeshInstance {
         queue: &wgpu::Queue,
         name: &str,
         mesh: std::rc::Rc<Mesh>,
+        primitives: Vec<MeshInstancePrimitive>,
         transform_matrix: cgmath::Matrix4<f32>,
         transform_bind_group_layout: &wgpu::BindGroupLayout,
     ) -> Self {
         let gpu_transform_uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some(&format!("{name}_TRANSFORM_UNIFORM_BUFFER")),
-            size: std::mem::size_of::<[[f32; 4]; 4]>() as u64,
+            size: std::mem::size_of::<PrimitiveInstanceUniform>() as u64,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
 
-        let transform_data: [[f32; 4]; 4] = transform_matrix.into();
-
         queue.write_buffer(
             &gpu_transform_uniform_buffer,
             0,
-            bytemuck::cast_slice(&[transform_data]),
+            bytemuck::cast_slice(&[PrimitiveInstanceUniform::new(transform_matrix)]),
         );
 
         let gpu_transform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -46,8 +57,32 @@ impl MeshInstance {
 
         Self {
             mesh,
+            primitives,
             gpu_transform_uniform_buffer,
             gpu_transform_bind_group,
+            skin: None,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PrimitiveInstanceUniform {
+    transform_matrix: [[f32; 4]; 4],
+    normal_matrix: [[f32; 4]; 4],
+}
+
+impl PrimitiveInstanceUniform {
+    pub fn new(transform_matrix: cgmath::Matrix4<f32>) -> Self {
+        // Normals have to be transformed with the inverse-transpose to stay perpendicular to non-uniformly scaled surfaces
+        let normal_matrix = transform_matrix
+            .invert()
+            .unwrap_or(cgmath::Matrix4::identity())
+            .transpose();
+
+        Self {
+            transform_matrix: transform_matrix.into(),
+            normal_matrix: normal_matrix.into(),
         }
     }
 }
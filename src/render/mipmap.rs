@@ -0,0 +1,203 @@
+use anyhow::Result;
+
+use crate::error::Error;
+use crate::render::shader::ShaderModulePackage;
+
+pub struct MipmapGenerator {
+    device: std::rc::Rc<wgpu::Device>,
+    queue: std::rc::Rc<wgpu::Queue>,
+    gpu_pipelines: std::collections::HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+    source_bind_group_layout: wgpu::BindGroupLayout,
+    gpu_sampler: wgpu::Sampler,
+}
+
+impl MipmapGenerator {
+    pub fn from_device(
+        device: std::rc::Rc<wgpu::Device>,
+        queue: std::rc::Rc<wgpu::Queue>,
+        tera: &tera::Tera,
+    ) -> Result<Self> {
+        let source_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("MIPMAP_BIND_GROUP_LAYOUT"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("MIPMAP_RENDER_PIPELINE_LAYOUT"),
+                bind_group_layouts: &[&source_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let shader_module_package = ShaderModulePackage::from_templates(
+            "mipmap/fullscreen.vert",
+            "mipmap/mipmap.frag",
+            "MIPMAP",
+            &device,
+            tera,
+            None,
+        )?;
+
+        let gpu_pipelines = [
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+        ]
+        .into_iter()
+        .map(|format| {
+            let gpu_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(&format!("MIPMAP_{format:?}_RENDER_PIPELINE")),
+                layout: Some(&render_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module_package.vertex_shader_module,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module_package.fragment_shader_module,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState {
+                            color: wgpu::BlendComponent::REPLACE,
+                            alpha: wgpu::BlendComponent::REPLACE,
+                        }),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: Some(wgpu::Face::Back),
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            });
+
+            (format, gpu_pipeline)
+        })
+        .collect();
+
+        let gpu_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("MIPMAP_SAMPLER"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Ok(Self {
+            device,
+            queue,
+            gpu_pipelines,
+            source_bind_group_layout,
+            gpu_sampler,
+        })
+    }
+
+    pub fn generate_mipmaps(&self, name: &str, texture: &wgpu::Texture) -> Result<()> {
+        let gpu_pipeline = match self.gpu_pipelines.get(&texture.format()) {
+            Some(gpu_pipeline) => gpu_pipeline,
+            None => {
+                return Err(Error::new(format!(
+                    "Unable to generate mipmaps for texture format: {:?}",
+                    texture.format()
+                ))
+                .into())
+            }
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some(&format!("{name}_MIPMAP_COMMAND_ENCODER")),
+            });
+
+        // Each mip level is downsampled from the one directly above it
+        for mip_level in 1..texture.mip_level_count() {
+            let source_texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some(&format!("{name}_MIP_{}_TEXTURE_VIEW", mip_level - 1)),
+                base_mip_level: mip_level - 1,
+                mip_level_count: Some(1),
+                ..Default::default()
+            });
+
+            let target_texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some(&format!("{name}_MIP_{mip_level}_TEXTURE_VIEW")),
+                base_mip_level: mip_level,
+                mip_level_count: Some(1),
+                ..Default::default()
+            });
+
+            let gpu_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(&format!("{name}_MIP_{mip_level}_BIND_GROUP")),
+                layout: &self.source_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source_texture_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.gpu_sampler),
+                    },
+                ],
+            });
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(&format!("{name}_MIP_{mip_level}_RENDER_PASS")),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target_texture_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            render_pass.set_pipeline(gpu_pipeline);
+            render_pass.set_bind_group(0, &gpu_bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        Ok(())
+    }
+}
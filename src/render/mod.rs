@@ -1,10 +1,13 @@
 use anyhow::Result;
+use cgmath::MetricSpace;
 
 use crate::data::transform::Transform;
-use crate::error::Error;
 use crate::render::cubemap::CubeMap;
 use crate::render::ibl::IblEnvironment;
 use crate::render::lut::GgxLut;
+use crate::render::material::AlphaMode;
+use crate::render::mesh::MeshInstance;
+use crate::render::pipeline::RenderPipeline;
 use crate::render::primitive::Primitive;
 use crate::render::scene::SceneLoader;
 use crate::render::state::RenderSystemState;
@@ -17,20 +20,24 @@ mod buffer;
 mod camera;
 mod cubemap;
 mod equirectangular;
+mod geometry;
 mod ibl;
 mod image;
 mod lut;
 mod material;
 mod mesh;
+mod mipmap;
 mod node;
 mod pipeline;
 mod primitive;
 mod sampler;
 mod scene;
 mod shader;
+mod skin;
 mod skybox;
 mod state;
 mod storage;
+mod target;
 mod texture;
 mod view;
 
@@ -40,8 +47,29 @@ pub struct RenderSystem {
 }
 
 impl RenderSystem {
-    pub async fn from_window(window: std::sync::Arc<winit::window::Window>) -> Result<Self> {
-        let state = RenderSystemState::from_window(window).await?;
+    pub async fn from_window(
+        window: std::sync::Arc<winit::window::Window>,
+        sample_count: u32,
+    ) -> Result<Self> {
+        let state = RenderSystemState::from_window(window, sample_count).await?;
+
+        Ok(Self {
+            state,
+            storage: Default::default(),
+        })
+    }
+
+    pub async fn from_dimensions(
+        view_dimensions: winit::dpi::PhysicalSize<u32>,
+        force_fallback_adapter: bool,
+        sample_count: u32,
+    ) -> Result<Self> {
+        let state = RenderSystemState::from_dimensions(
+            view_dimensions,
+            force_fallback_adapter,
+            sample_count,
+        )
+        .await?;
 
         Ok(Self {
             state,
@@ -68,12 +96,35 @@ impl RenderSystem {
     }
 
     pub fn render(&mut self) -> Result<()> {
-        let output = self.state.surface.get_current_texture()?;
+        let output = self.state.target.get_current_surface_texture()?;
 
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
+        let encoder = self.encode_frame(&view)?;
+
+        self.state.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+
+    pub fn render_to_image(&mut self) -> Result<::image::RgbaImage> {
+        let view = self
+            .state
+            .target
+            .get_texture()?
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let encoder = self.encode_frame(&view)?;
+
+        self.state
+            .target
+            .read_image(&self.state.device, &self.state.queue, encoder)
+    }
+
+    fn encode_frame(&self, view: &wgpu::TextureView) -> Result<wgpu::CommandEncoder> {
         let mut encoder =
             self.state
                 .device
@@ -81,12 +132,23 @@ impl RenderSystem {
                     label: Some("RENDER_SYSTEM_COMMAND_ENCODER"),
                 });
 
+        // Multisampled frames are rendered offscreen and only the resolved result is kept
+        let (color_view, resolve_target, color_store) = match &self.state.multisampled_color_texture
+        {
+            Some(multisampled_color_texture) => (
+                &multisampled_color_texture.gpu_texture_view,
+                Some(view),
+                wgpu::StoreOp::Discard,
+            ),
+            None => (view, None, wgpu::StoreOp::Store),
+        };
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("RENDER_SYSTEM_RENDER_PASS"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
+                    view: color_view,
+                    resolve_target,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
                             r: 0.1,
@@ -94,7 +156,7 @@ impl RenderSystem {
                             b: 0.3,
                             a: 1.0,
                         }),
-                        store: wgpu::StoreOp::Store,
+                        store: color_store,
                     },
                 })],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
@@ -109,16 +171,38 @@ impl RenderSystem {
                 timestamp_writes: None,
             });
 
+            let camera_position = self.state.view_environment.camera_position();
+            let mut blended_primitives =
+                Vec::<(f32, &Primitive, &RenderPipeline, &MeshInstance)>::new();
+
             for node in self.storage.node_registry.values() {
                 let mesh_instance = match &node.mesh {
                     Some(mesh_instance) => mesh_instance,
                     None => continue,
                 };
 
-                for primitive in mesh_instance.mesh.primitives.iter() {
+                for (primitive, instance_primitive) in mesh_instance
+                    .mesh
+                    .primitives
+                    .iter()
+                    .zip(mesh_instance.primitives.iter())
+                {
+                    let render_pipeline = &instance_primitive.render_pipeline;
+
+                    if render_pipeline.config.alpha_mode == AlphaMode::Blend {
+                        blended_primitives.push((
+                            instance_primitive.centroid.distance2(camera_position),
+                            primitive,
+                            render_pipeline,
+                            mesh_instance,
+                        ));
+                        continue;
+                    }
+
                     self.render_primitive(
                         primitive,
-                        &mesh_instance.gpu_transform_bind_group,
+                        render_pipeline,
+                        mesh_instance,
                         &mut render_pass,
                     )?;
                 }
@@ -127,46 +211,49 @@ impl RenderSystem {
             self.state
                 .skybox_renderer
                 .render_skybox(self.state.view_environment.skybox(), &mut render_pass);
-        }
 
-        self.state.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+            // Blended primitives are drawn last and back-to-front, so that they are composited over everything behind them
+            blended_primitives.sort_by(|a, b| b.0.total_cmp(&a.0));
 
-        Ok(())
+            for (_, primitive, render_pipeline, mesh_instance) in blended_primitives {
+                self.render_primitive(primitive, render_pipeline, mesh_instance, &mut render_pass)?;
+            }
+        }
+
+        Ok(encoder)
     }
 
     fn render_primitive(
         &self,
         primitive: &Primitive,
-        gpu_transform_bind_group: &wgpu::BindGroup,
+        render_pipeline: &RenderPipeline,
+        mesh_instance: &MeshInstance,
         render_pass: &mut wgpu::RenderPass,
     ) -> Result<()> {
-        render_pass.set_pipeline(&primitive.render_pipeline.gpu_pipeline);
+        render_pass.set_pipeline(&render_pipeline.gpu_pipeline);
 
         for buffer_segment in primitive.vertex_buffer.segments.iter() {
             let location = match buffer_segment.type_ {
                 gltf::Semantic::Positions => 0,
                 gltf::Semantic::Normals => 1,
                 gltf::Semantic::Tangents => 2,
-                gltf::Semantic::TexCoords(index) => {
-                    match index {
-                        0 => primitive.render_pipeline.config.get_tex_coord_0_location(),
-                        1 => primitive.render_pipeline.config.get_tex_coord_1_location(),
-                        _ => return Err(
-                            Error::new(format!("The given primitive has a texture coordinate attribute with an index greater than 1: {index}")).into()
-                        ),
-                    }
-                }
+                gltf::Semantic::TexCoords(index) => render_pipeline
+                    .config
+                    .get_tex_coord_location(index as usize),
                 gltf::Semantic::Colors(index) => {
-                    match index {
-                        0 => primitive.render_pipeline.config.get_color_0_location(),
-                        _ => return Err(
-                            Error::new(format!("The given primitive has a vertex color attribute with an index greater than 0: {index}")).into()
-                        ),
-                    }
+                    render_pipeline.config.get_color_location(index as usize)
+                }
+                gltf::Semantic::Joints(_) if render_pipeline.config.skin_format.is_some() => {
+                    render_pipeline.config.get_joints_location()
+                }
+                gltf::Semantic::Weights(_) if render_pipeline.config.skin_format.is_some() => {
+                    render_pipeline.config.get_weights_location()
                 }
                 _ => {
-                    log::info!("Ignoring unsupported vertex attribute type: {:?}", buffer_segment.type_);
+                    log::info!(
+                        "Ignoring unsupported vertex attribute type: {:?}",
+                        buffer_segment.type_
+                    );
                     continue;
                 }
             };
@@ -181,9 +268,13 @@ impl RenderSystem {
         }
 
         render_pass.set_bind_group(0, self.state.view_environment.bind_group(), &[]);
-        render_pass.set_bind_group(1, gpu_transform_bind_group, &[]);
+        render_pass.set_bind_group(1, &mesh_instance.gpu_transform_bind_group, &[]);
         render_pass.set_bind_group(2, &primitive.material.gpu_bind_group, &[]);
 
+        if let (Some(_), Some(skin)) = (render_pipeline.config.skin_format, &mesh_instance.skin) {
+            render_pass.set_bind_group(3, &skin.gpu_bind_group, &[]);
+        }
+
         match &primitive.index_buffer {
             Some(index_buffer) => {
                 render_pass.set_index_buffer(index_buffer.gpu_buffer.slice(..), index_buffer.type_);
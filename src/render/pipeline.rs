@@ -1,3 +1,5 @@
+use crate::render::material::{AlphaMode, MaterialTexCoordSets};
+
 pub struct RenderPipeline {
     pub config: RenderPipelineConfiguration,
     pub gpu_pipeline: wgpu::RenderPipeline,
@@ -23,6 +25,18 @@ impl RenderPipeline {
         let vertex_buffer_layout_builder =
             RenderPipeline::create_vertex_buffer_layout_builder(&config);
 
+        // Blended primitives are composited over the opaque geometry without occluding each other
+        let (blend, depth_write_enabled) = match config.alpha_mode {
+            AlphaMode::Blend => (wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING, false),
+            AlphaMode::Opaque | AlphaMode::Mask => (
+                wgpu::BlendState {
+                    color: wgpu::BlendComponent::REPLACE,
+                    alpha: wgpu::BlendComponent::REPLACE,
+                },
+                true,
+            ),
+        };
+
         let gpu_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some(&format!("{name}_RENDER_PIPELINE")),
             layout: Some(&render_pipeline_layout),
@@ -37,10 +51,7 @@ impl RenderPipeline {
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
                     format,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent::REPLACE,
-                        alpha: wgpu::BlendComponent::REPLACE,
-                    }),
+                    blend: Some(blend),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
                 compilation_options: Default::default(),
@@ -48,21 +59,24 @@ impl RenderPipeline {
             primitive: wgpu::PrimitiveState {
                 topology: config.topology,
                 strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                front_face: config.front_face,
+                cull_mode: match config.double_sided {
+                    true => None,
+                    false => Some(wgpu::Face::Back),
+                },
                 polygon_mode: wgpu::PolygonMode::Fill,
                 unclipped_depth: false,
                 conservative: false,
             },
             depth_stencil: Some(wgpu::DepthStencilState {
                 format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: true,
+                depth_write_enabled,
                 depth_compare: wgpu::CompareFunction::Less,
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: config.sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -81,125 +95,105 @@ impl RenderPipeline {
     ) -> VertexBufferLayoutBuilder {
         let mut builder: VertexBufferLayoutBuilder = Default::default();
 
-        builder.add(VertexBufferLayoutBuilderEntry {
-            array_stride: (3 * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: vec![wgpu::VertexAttribute {
-                offset: 0,
-                shader_location: 0,
-                format: wgpu::VertexFormat::Float32x3,
-            }],
-        });
+        builder.add(VertexBufferLayoutBuilderEntry::from_format(
+            config.position_format,
+            0,
+        ));
 
-        if config.has_normal {
-            builder.add(VertexBufferLayoutBuilderEntry {
-                array_stride: (3 * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
-                step_mode: wgpu::VertexStepMode::Vertex,
-                attributes: vec![wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x3,
-                }],
-            });
+        if let Some(normal_format) = config.normal_format {
+            builder.add(VertexBufferLayoutBuilderEntry::from_format(
+                normal_format,
+                1,
+            ));
 
-            if config.has_tangent {
-                builder.add(VertexBufferLayoutBuilderEntry {
-                    array_stride: (4 * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
-                    step_mode: wgpu::VertexStepMode::Vertex,
-                    attributes: vec![wgpu::VertexAttribute {
-                        offset: 0,
-                        shader_location: 2,
-                        format: wgpu::VertexFormat::Float32x4,
-                    }],
-                });
+            if let Some(tangent_format) = config.tangent_format {
+                builder.add(VertexBufferLayoutBuilderEntry::from_format(
+                    tangent_format,
+                    2,
+                ));
             }
         }
 
-        if config.has_tex_coord_0 {
-            builder.add(VertexBufferLayoutBuilderEntry {
-                array_stride: (2 * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
-                step_mode: wgpu::VertexStepMode::Vertex,
-                attributes: vec![wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: config.get_tex_coord_0_location(),
-                    format: wgpu::VertexFormat::Float32x2,
-                }],
-            });
+        for (index, tex_coord_format) in config.tex_coord_formats.iter().enumerate() {
+            builder.add(VertexBufferLayoutBuilderEntry::from_format(
+                *tex_coord_format,
+                config.get_tex_coord_location(index),
+            ));
         }
 
-        if config.has_tex_coord_1 {
-            builder.add(VertexBufferLayoutBuilderEntry {
-                array_stride: (2 * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
-                step_mode: wgpu::VertexStepMode::Vertex,
-                attributes: vec![wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: config.get_tex_coord_1_location(),
-                    format: wgpu::VertexFormat::Float32x2,
-                }],
-            });
+        for (index, color_format) in config.color_formats.iter().enumerate() {
+            builder.add(VertexBufferLayoutBuilderEntry::from_format(
+                color_format.format,
+                config.get_color_location(index),
+            ));
         }
 
-        if config.has_color_0 {
-            builder.add(VertexBufferLayoutBuilderEntry {
-                array_stride: (4 * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
-                step_mode: wgpu::VertexStepMode::Vertex,
-                attributes: vec![wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: config.get_color_0_location(),
-                    format: wgpu::VertexFormat::Float32x4,
-                }],
-            });
+        if let Some(skin_format) = config.skin_format {
+            builder.add(VertexBufferLayoutBuilderEntry::from_format(
+                skin_format.joints_format,
+                config.get_joints_location(),
+            ));
+            builder.add(VertexBufferLayoutBuilderEntry::from_format(
+                skin_format.weights_format,
+                config.get_weights_location(),
+            ));
         }
 
         builder
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct RenderPipelineConfiguration {
-    pub has_normal: bool,
-    pub has_tangent: bool,
-    pub has_tex_coord_0: bool,
-    pub has_tex_coord_1: bool,
-    pub has_color_0: bool,
+    pub position_format: wgpu::VertexFormat,
+    pub normal_format: Option<wgpu::VertexFormat>,
+    pub tangent_format: Option<wgpu::VertexFormat>,
+    pub tex_coord_formats: Vec<wgpu::VertexFormat>,
+    pub color_formats: Vec<VertexColorFormat>,
+    pub skin_format: Option<VertexSkinFormat>,
+    pub has_normal_texture: bool,
+    pub tex_coord_sets: MaterialTexCoordSets,
+    pub alpha_mode: AlphaMode,
+    pub double_sided: bool,
+    pub front_face: wgpu::FrontFace,
     pub topology: wgpu::PrimitiveTopology,
+    pub sample_count: u32,
 }
 
 impl RenderPipelineConfiguration {
-    pub fn get_tex_coord_0_location(&self) -> u32 {
-        match self.has_tex_coord_0 {
-            true => self.get_base_location_offset(),
-            false => 0,
-        }
+    pub fn get_tex_coord_location(&self, index: usize) -> u32 {
+        self.get_base_location_offset() + index as u32
     }
 
-    pub fn get_tex_coord_1_location(&self) -> u32 {
-        match self.has_tex_coord_1 {
-            true => self.get_base_location_offset() + 1,
-            false => 0,
-        }
+    pub fn get_color_location(&self, index: usize) -> u32 {
+        self.get_base_location_offset() + (self.tex_coord_formats.len() + index) as u32
     }
 
-    pub fn get_color_0_location(&self) -> u32 {
-        if !self.has_color_0 {
-            return 0;
-        }
+    pub fn get_joints_location(&self) -> u32 {
+        self.get_base_location_offset()
+            + (self.tex_coord_formats.len() + self.color_formats.len()) as u32
+    }
 
-        let base_offset = self.get_base_location_offset();
+    pub fn get_weights_location(&self) -> u32 {
+        self.get_joints_location() + 1
+    }
 
-        if self.has_tex_coord_1 {
-            base_offset + 2
-        } else if self.has_tex_coord_0 {
-            base_offset + 1
-        } else {
-            base_offset
-        }
+    pub fn is_lit(&self) -> bool {
+        Self::is_lit_topology(self.topology)
+    }
+
+    // Points and lines have no surface that could be lit
+    pub fn is_lit_topology(topology: wgpu::PrimitiveTopology) -> bool {
+        matches!(
+            topology,
+            wgpu::PrimitiveTopology::TriangleList | wgpu::PrimitiveTopology::TriangleStrip
+        )
     }
 
     fn get_base_location_offset(&self) -> u32 {
-        if self.has_tangent {
+        if self.tangent_format.is_some() {
             3
-        } else if self.has_normal {
+        } else if self.normal_format.is_some() {
             2
         } else {
             1
@@ -207,6 +201,18 @@ impl RenderPipelineConfiguration {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct VertexColorFormat {
+    pub format: wgpu::VertexFormat,
+    pub has_alpha: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct VertexSkinFormat {
+    pub joints_format: wgpu::VertexFormat,
+    pub weights_format: wgpu::VertexFormat,
+}
+
 #[derive(Default)]
 struct VertexBufferLayoutBuilder {
     entries: Vec<VertexBufferLayoutBuilderEntry>,
@@ -234,3 +240,89 @@ struct VertexBufferLayoutBuilderEntry {
     step_mode: wgpu::VertexStepMode,
     attributes: Vec<wgpu::VertexAttribute>,
 }
+
+impl VertexBufferLayoutBuilderEntry {
+    pub fn from_format(format: wgpu::VertexFormat, shader_location: u32) -> Self {
+        Self {
+            array_stride: format.size(),
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: vec![wgpu::VertexAttribute {
+                offset: 0,
+                shader_location,
+                format,
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RenderPipeline, RenderPipelineConfiguration, VertexColorFormat, VertexSkinFormat};
+    use crate::render::material::{AlphaMode, MaterialTexCoordSets};
+
+    fn skinned_config(
+        normal_format: Option<wgpu::VertexFormat>,
+        tangent_format: Option<wgpu::VertexFormat>,
+        tex_coord_set_count: usize,
+        color_set_count: usize,
+    ) -> RenderPipelineConfiguration {
+        RenderPipelineConfiguration {
+            position_format: wgpu::VertexFormat::Float32x3,
+            normal_format,
+            tangent_format,
+            tex_coord_formats: vec![wgpu::VertexFormat::Float32x2; tex_coord_set_count],
+            color_formats: vec![
+                VertexColorFormat {
+                    format: wgpu::VertexFormat::Float32x4,
+                    has_alpha: true,
+                };
+                color_set_count
+            ],
+            skin_format: Some(VertexSkinFormat {
+                joints_format: wgpu::VertexFormat::Uint8x4,
+                weights_format: wgpu::VertexFormat::Float32x4,
+            }),
+            has_normal_texture: false,
+            tex_coord_sets: MaterialTexCoordSets {
+                base_color: 0,
+                metallic_roughness: 0,
+                normal: 0,
+                occlusion: 0,
+                emissive: 0,
+            },
+            alpha_mode: AlphaMode::Opaque,
+            double_sided: false,
+            front_face: wgpu::FrontFace::Ccw,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            sample_count: 1,
+        }
+    }
+
+    #[test]
+    fn skin_locations_follow_tex_coords_and_colors() {
+        let config = skinned_config(
+            Some(wgpu::VertexFormat::Float32x3),
+            Some(wgpu::VertexFormat::Float32x4),
+            2,
+            1,
+        );
+
+        assert_eq!(config.get_joints_location(), 6);
+        assert_eq!(config.get_weights_location(), 7);
+
+        let locations = RenderPipeline::create_vertex_buffer_layout_builder(&config)
+            .entries
+            .iter()
+            .map(|entry| entry.attributes[0].shader_location)
+            .collect::<Vec<_>>();
+        assert_eq!(locations, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn skin_locations_follow_positions_without_normals() {
+        let config = skinned_config(None, None, 0, 0);
+
+        assert_eq!(config.get_joints_location(), 1);
+        assert_eq!(config.get_weights_location(), 2);
+    }
+}
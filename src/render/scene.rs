@@ -1,27 +1,54 @@
 use anyhow::Result;
+use cgmath::{EuclideanSpace, SquareMatrix, Transform as _};
 
 use crate::data::transform::Transform;
 use crate::error::Error;
 use crate::render::buffer::allocator::{VertexBufferAllocator, VertexBufferSegmentDataSource};
 use crate::render::buffer::IndexBuffer;
 use crate::render::camera::Camera;
+use crate::render::geometry::PrimitiveGeometry;
 use crate::render::image::Image;
-use crate::render::material::{Material, MetallicRoughnessUniform};
-use crate::render::mesh::{Mesh, MeshInstance};
+use crate::render::material::{
+    AlphaMode, Material, MaterialParameters, MaterialTextureTransforms, MetallicRoughnessUniform,
+    TextureTransform,
+};
+use crate::render::mesh::{Mesh, MeshInstance, MeshInstancePrimitive};
 use crate::render::node::RenderNode;
-use crate::render::pipeline::{RenderPipeline, RenderPipelineConfiguration};
+use crate::render::pipeline::{
+    RenderPipeline, RenderPipelineConfiguration, VertexColorFormat, VertexSkinFormat,
+};
 use crate::render::primitive::Primitive;
 use crate::render::sampler::Sampler;
-use crate::render::shader::{ShaderModulePackage, ShaderTemplateConfiguration};
+use crate::render::shader::{ShaderModulePackage, ShaderScalarType, ShaderTemplateConfiguration};
+use crate::render::skin::{Skin, SkinOrientation};
 use crate::render::state::RenderSystemState;
 use crate::render::storage::RenderSystemSceneStorage;
 use crate::render::texture::Texture2DPackage;
 use crate::resource::gltf::loader::GltfLoader;
 
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TextureTransformExtension {
+    #[serde(default)]
+    offset: [f32; 2],
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default = "TextureTransformExtension::default_scale")]
+    scale: [f32; 2],
+    tex_coord: Option<u32>,
+}
+
+impl TextureTransformExtension {
+    fn default_scale() -> [f32; 2] {
+        [1.0, 1.0]
+    }
+}
+
 pub struct SceneLoader<'a, T: GltfLoader> {
     state: &'a RenderSystemState,
     storage: &'a mut RenderSystemSceneStorage,
     gltf_loader: &'a mut T,
+    global_transform_matrices: std::collections::HashMap<usize, cgmath::Matrix4<f32>>,
 }
 
 impl<'a, T: GltfLoader> SceneLoader<'a, T> {
@@ -35,6 +62,7 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
             state,
             storage,
             gltf_loader,
+            global_transform_matrices: Default::default(),
         };
         scene_loader.load_scene(scene)?;
 
@@ -48,8 +76,17 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
             scene.index(),
         );
 
+        // Joints may be located anywhere within the scene, so the global transforms of all nodes are collected before any node or skin is loaded
+        for node in scene.nodes() {
+            Self::collect_global_transform_matrices(
+                &node,
+                cgmath::Matrix4::identity(),
+                &mut self.global_transform_matrices,
+            );
+        }
+
         for node in scene.nodes() {
-            self.load_node(&node, None)?;
+            self.load_node(&node)?;
         }
 
         self.state.queue.submit([]);
@@ -57,11 +94,35 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
         Ok(())
     }
 
-    fn load_node(
-        &mut self,
+    fn collect_global_transform_matrices(
         node: &gltf::Node,
-        parent_transform_matrix: Option<cgmath::Matrix4<f32>>,
-    ) -> Result<std::rc::Rc<RenderNode>> {
+        parent_transform_matrix: cgmath::Matrix4<f32>,
+        global_transform_matrices: &mut std::collections::HashMap<usize, cgmath::Matrix4<f32>>,
+    ) {
+        let global_transform_matrix =
+            parent_transform_matrix * cgmath::Matrix4::from(Self::get_local_transform(node));
+        global_transform_matrices.insert(node.index(), global_transform_matrix);
+
+        for child in node.children() {
+            Self::collect_global_transform_matrices(
+                &child,
+                global_transform_matrix,
+                global_transform_matrices,
+            );
+        }
+    }
+
+    fn get_local_transform(node: &gltf::Node) -> Transform {
+        let (translation, rotation, scale) = node.transform().decomposed();
+
+        Transform {
+            translation: translation.into(),
+            rotation: rotation.into(),
+            scale: scale.into(),
+        }
+    }
+
+    fn load_node(&mut self, node: &gltf::Node) -> Result<std::rc::Rc<RenderNode>> {
         if self.storage.node_registry.contains_key(&node.index()) {
             return Err(Error::new(format!(
                 "A node with the given ID has already been registered: {}",
@@ -81,23 +142,22 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
             node.index()
         );
 
-        let (translation, rotation, scale) = node.transform().decomposed();
+        let local_transform = Self::get_local_transform(node);
 
-        let local_transform = Transform {
-            translation: translation.into(),
-            rotation: rotation.into(),
-            scale: scale.into(),
-        };
-        let local_transform_matrix = cgmath::Matrix4::from(local_transform);
-
-        let global_transform_matrix = match parent_transform_matrix {
-            Some(parent_transform_matrix) => parent_transform_matrix * local_transform_matrix,
-            None => local_transform_matrix,
+        let global_transform_matrix = match self.global_transform_matrices.get(&node.index()) {
+            Some(global_transform_matrix) => *global_transform_matrix,
+            None => {
+                return Err(Error::new(format!(
+                    "The global transform of the given node has not been collected: {}",
+                    node.index()
+                ))
+                .into())
+            }
         };
 
         let mut children: Vec<std::rc::Rc<RenderNode>> = vec![];
         for child in node.children() {
-            children.push(self.load_node(&child, Some(global_transform_matrix))?);
+            children.push(self.load_node(&child)?);
         }
 
         let mesh_instance = match node.mesh() {
@@ -112,16 +172,55 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
                     mesh.index(),
                 );
 
+                let gltf_primitives = mesh.primitives().collect::<Vec<_>>();
                 let mesh = self.load_mesh(&mesh)?;
 
-                Some(MeshInstance::from_device(
+                let skin = match node.skin() {
+                    Some(skin) => Some(self.load_skin(&skin)?),
+                    None => None,
+                };
+
+                // Transforms with a negative determinant mirror the mesh, which inverts the winding order of its faces
+                let mirrored = global_transform_matrix.determinant() < 0.0;
+
+                let mut primitives =
+                    Vec::<MeshInstancePrimitive>::with_capacity(gltf_primitives.len());
+                for (primitive, gltf_primitive) in mesh.primitives.iter().zip(gltf_primitives) {
+                    // Primitives without joints and weights are not skinned, even when their node references a skin
+                    let primitive_skin = match primitive.render_pipeline_config.skin_format {
+                        Some(_) => skin.as_deref(),
+                        None => None,
+                    };
+
+                    primitives.push(match primitive_skin {
+                        Some(skin) => MeshInstancePrimitive {
+                            render_pipeline: self.get_instance_render_pipeline(
+                                primitive,
+                                false,
+                                Some(skin.orientation),
+                            )?,
+                            centroid: self.get_skinned_centroid(&gltf_primitive, skin)?,
+                        },
+                        None => MeshInstancePrimitive {
+                            render_pipeline: self
+                                .get_instance_render_pipeline(primitive, mirrored, None)?,
+                            centroid: global_transform_matrix.transform_point(primitive.centroid),
+                        },
+                    });
+                }
+
+                let mut mesh_instance = MeshInstance::from_device(
                     &self.state.device,
                     &self.state.queue,
                     &mesh_instance_name,
                     mesh,
+                    primitives,
                     global_transform_matrix,
                     &self.state.primitive_instance_bind_group_layout,
-                ))
+                );
+                mesh_instance.skin = skin;
+
+                Some(mesh_instance)
             }
             None => None,
         };
@@ -191,6 +290,100 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
         Ok(loaded_mesh)
     }
 
+    fn load_skin(&mut self, skin: &gltf::Skin) -> Result<std::rc::Rc<Skin>> {
+        let skin_log_name = format!(
+            "{} - [{}]",
+            skin.name().unwrap_or("<UNNAMED>"),
+            skin.index(),
+        );
+
+        if let Some(skin) = self.storage.skin_registry.get(&skin.index()) {
+            log::debug!("Skipping duplicate load of glTF skin: {skin_log_name}");
+            return Ok(skin.clone());
+        }
+
+        log::debug!("Loading glTF skin: {skin_log_name}");
+
+        let skin_name_string = match skin.name() {
+            Some(name) => name.to_string(),
+            None => "<UNNAMED>".to_string(),
+        };
+        let skin_label = format!("SKIN_{skin_name_string}_{}", skin.index());
+
+        let joints = skin.joints().collect::<Vec<_>>();
+
+        let inverse_bind_matrices = match skin.inverse_bind_matrices() {
+            Some(accessor) => {
+                let data = self
+                    .gltf_loader
+                    .load_bytes_from_accessor(accessor.index())?;
+
+                PrimitiveGeometry::read_elements::<16>(data, &accessor)?
+                    .iter()
+                    .map(|elements| *<&cgmath::Matrix4<f32>>::from(elements))
+                    .collect::<Vec<_>>()
+            }
+            None => vec![cgmath::Matrix4::identity(); joints.len()],
+        };
+
+        if inverse_bind_matrices.len() < joints.len() {
+            return Err(Error::new(format!(
+                "The given skin has fewer inverse bind matrices than joints: {skin_log_name}"
+            ))
+            .into());
+        }
+
+        let joint_transform_matrices = joints
+            .iter()
+            .map(
+                |joint| match self.global_transform_matrices.get(&joint.index()) {
+                    Some(global_transform_matrix) => Ok(*global_transform_matrix),
+                    None => Err(Error::new(format!(
+                        "The joint {} of the given skin is not part of the scene: {skin_log_name}",
+                        joint.index()
+                    ))
+                    .into()),
+                },
+            )
+            .collect::<Result<Vec<_>>>()?;
+
+        let loaded_skin = std::rc::Rc::new(Skin::from_device(
+            &self.state.device,
+            &self.state.queue,
+            &skin_label,
+            Skin::get_joint_matrices(&joint_transform_matrices, &inverse_bind_matrices),
+            &self.state.skin_bind_group_layout,
+        )?);
+
+        self.storage
+            .skin_registry
+            .insert(skin.index(), loaded_skin.clone());
+
+        Ok(loaded_skin)
+    }
+
+    fn get_skinned_centroid(
+        &mut self,
+        primitive: &gltf::Primitive,
+        skin: &Skin,
+    ) -> Result<cgmath::Point3<f32>> {
+        let positions = self.read_attribute::<3>(primitive, &gltf::Semantic::Positions, None)?;
+        let joints = self.read_attribute::<4>(primitive, &gltf::Semantic::Joints(0), None)?;
+        let weights = self.read_attribute::<4>(primitive, &gltf::Semantic::Weights(0), None)?;
+
+        // Like the centroid of unskinned primitives, this is the center of the bounding box, but after skinning
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for ((position, joints), weights) in positions.into_iter().zip(joints).zip(weights) {
+            let position = Skin::skin_position(&skin.joint_matrices, position, joints, weights)?;
+
+            min = std::array::from_fn(|index| min[index].min(position[index]));
+            max = std::array::from_fn(|index| max[index].max(position[index]));
+        }
+
+        Ok(cgmath::Point3::from(min).midpoint(max.into()))
+    }
+
     fn load_primitive(
         &mut self,
         primitive: &gltf::Primitive,
@@ -199,72 +392,205 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
         let mut vertex_buffer_allocator =
             VertexBufferAllocator::new(format!("{label_prefix}_VERTEX_BUFFER"));
 
-        let topology = match primitive.mode() {
-            gltf::mesh::Mode::Triangles => wgpu::PrimitiveTopology::TriangleList,
-            _ => {
-                return Err(Error::new(format!(
-                    "The given primitive uses an unsupported topology: {:?}",
-                    primitive.mode()
-                ))
-                .into())
+        let has_normals = primitive.get(&gltf::Semantic::Normals).is_some();
+        let needs_tangents = primitive.material().normal_texture().is_some()
+            && primitive.get(&gltf::Semantic::Tangents).is_none();
+
+        // Loops and fans have no matching topology, and flat normals and tangents are generated per triangle of a list
+        let (topology, converted_indices) = match primitive.mode() {
+            gltf::mesh::Mode::Points => (wgpu::PrimitiveTopology::PointList, None),
+            gltf::mesh::Mode::Lines => (wgpu::PrimitiveTopology::LineList, None),
+            gltf::mesh::Mode::LineLoop => (
+                wgpu::PrimitiveTopology::LineList,
+                Some(PrimitiveGeometry::convert_line_loop(
+                    &self.read_primitive_indices(primitive)?,
+                )),
+            ),
+            gltf::mesh::Mode::LineStrip => (wgpu::PrimitiveTopology::LineStrip, None),
+            gltf::mesh::Mode::Triangles => (wgpu::PrimitiveTopology::TriangleList, None),
+            gltf::mesh::Mode::TriangleStrip if has_normals && !needs_tangents => {
+                (wgpu::PrimitiveTopology::TriangleStrip, None)
             }
+            gltf::mesh::Mode::TriangleStrip => (
+                wgpu::PrimitiveTopology::TriangleList,
+                Some(PrimitiveGeometry::convert_triangle_strip(
+                    &self.read_primitive_indices(primitive)?,
+                )),
+            ),
+            gltf::mesh::Mode::TriangleFan => (
+                wgpu::PrimitiveTopology::TriangleList,
+                Some(PrimitiveGeometry::convert_triangle_fan(
+                    &self.read_primitive_indices(primitive)?,
+                )),
+            ),
+        };
+        let is_lit = RenderPipelineConfiguration::is_lit_topology(topology);
+
+        // Flat normals and generated tangents differ between faces, so vertices shared by multiple faces have to be split up
+        let (vertex_indices, converted_indices) = match (
+            (!has_normals || needs_tangents) && is_lit,
+            converted_indices,
+        ) {
+            (true, Some(converted_indices)) => (Some(converted_indices), None),
+            (true, None) => match primitive.indices() {
+                Some(accessor) => (Some(self.read_indices(&accessor)?), None),
+                None => (None, None),
+            },
+            (false, converted_indices) => (None, converted_indices),
         };
 
-        let mut has_position = false;
-        let mut has_normal = false;
-        let mut has_tangent = false;
-        let mut has_tex_coord_0 = false;
-        let mut has_tex_coord_1 = false;
-        let mut has_color_0 = false;
-
-        for (semantic, _) in primitive.attributes() {
-            match semantic {
-                gltf::Semantic::Positions => has_position = true,
-                gltf::Semantic::Normals => has_normal = true,
-                gltf::Semantic::Tangents => has_tangent = true,
-                gltf::Semantic::TexCoords(index) => {
-                    match index {
-                        0 => has_tex_coord_0 = true,
-                        1 => has_tex_coord_1 = true,
-                        _ => return Err(
-                            Error::new(format!("The given primitive has a texture coordinate attribute with an index greater than 1: {index}")).into()
-                        ),
-                    }
+        // Only a single set of joints and weights is supported, which limits each vertex to four joints
+        let has_skin = primitive.get(&gltf::Semantic::Joints(0)).is_some()
+            && primitive.get(&gltf::Semantic::Weights(0)).is_some();
+
+        // Vertex buffers are reserved for positions, normals and tangents, since missing normals and tangents may be generated,
+        // as well as for the joints and weights of skinned primitives
+        let limits = self.state.device.limits();
+        let available_set_count = limits
+            .max_vertex_buffers
+            .min(limits.max_vertex_attributes)
+            .saturating_sub(if has_skin { 5 } else { 3 })
+            as usize;
+
+        let tex_coord_set_count =
+            Self::get_attribute_set_count(primitive, gltf::Semantic::TexCoords)
+                .min(available_set_count);
+        let color_set_count = Self::get_attribute_set_count(primitive, gltf::Semantic::Colors)
+            .min(available_set_count - tex_coord_set_count);
+
+        let mut position_format = None;
+        let mut normal_format = None;
+        let mut tangent_format = None;
+        let mut tex_coord_formats = vec![None; tex_coord_set_count];
+        let mut color_formats = vec![None; color_set_count];
+        let mut color_alphas = vec![false; color_set_count];
+        let mut joints_format = None;
+        let mut weights_format = None;
+
+        for (semantic, accessor) in primitive.attributes() {
+            let format = match semantic {
+                gltf::Semantic::Positions => &mut position_format,
+                gltf::Semantic::Normals => &mut normal_format,
+                gltf::Semantic::Tangents => &mut tangent_format,
+                gltf::Semantic::TexCoords(index) if (index as usize) < tex_coord_set_count => {
+                    &mut tex_coord_formats[index as usize]
                 }
-                gltf::Semantic::Colors(index) => {
-                    match index {
-                        0 => has_color_0 = true,
-                        _ => return Err(
-                            Error::new(format!("The given primitive has a vertex color attribute with an index greater than 0: {index}")).into()
-                        ),
-                    }
+                gltf::Semantic::Colors(index) if (index as usize) < color_set_count => {
+                    color_alphas[index as usize] =
+                        accessor.dimensions() == gltf::accessor::Dimensions::Vec4;
+                    &mut color_formats[index as usize]
+                }
+                gltf::Semantic::TexCoords(_) | gltf::Semantic::Colors(_) => {
+                    log::warn!(
+                        "Ignoring {:?} attribute of {label_prefix}, since it exceeds the vertex attribute limits of the device or does not follow a contiguous set.",
+                        semantic
+                    );
+                    continue;
                 }
-                _ => {}
+                gltf::Semantic::Joints(0) if has_skin => &mut joints_format,
+                gltf::Semantic::Weights(0) if has_skin => &mut weights_format,
+                gltf::Semantic::Joints(_) | gltf::Semantic::Weights(_) => {
+                    log::warn!(
+                        "Ignoring {:?} attribute of {label_prefix}, since only a single complete set of joints and weights is supported.",
+                        semantic
+                    );
+                    continue;
+                }
+            };
+
+            let vertex_format = Self::get_vertex_format(&accessor)?;
+
+            // Only positions and texture coordinates may be stored as unnormalized integers
+            if matches!(
+                semantic,
+                gltf::Semantic::Normals
+                    | gltf::Semantic::Tangents
+                    | gltf::Semantic::Colors(_)
+                    | gltf::Semantic::Weights(_)
+            ) && ShaderScalarType::from_vertex_format(vertex_format) != ShaderScalarType::F32
+            {
+                return Err(Error::new(format!(
+                    "The given primitive has a {:?} attribute with an unnormalized integer data type: {:?}",
+                    semantic,
+                    accessor.data_type()
+                ))
+                .into());
             }
-        }
 
-        if !has_position {
-            return Err(
-                Error::new("The given primitive has no position attribute.".to_string()).into(),
-            );
+            // Joints are indices into the joint matrices of a skin
+            if matches!(semantic, gltf::Semantic::Joints(_))
+                && ShaderScalarType::from_vertex_format(vertex_format) != ShaderScalarType::U32
+            {
+                return Err(Error::new(format!(
+                    "The given primitive has a {:?} attribute that is not an unsigned integer data type: {:?}",
+                    semantic,
+                    accessor.data_type()
+                ))
+                .into());
+            }
+
+            *format = Some(vertex_format);
+
+            let data_source =
+                self.get_vertex_data_source(&accessor, vertex_format, vertex_indices.as_deref())?;
+            vertex_buffer_allocator.add_segment(semantic, data_source);
         }
 
-        for (semantic, accessor) in primitive.attributes() {
-            match semantic {
-                gltf::Semantic::Positions
-                | gltf::Semantic::Normals
-                | gltf::Semantic::Tangents
-                | gltf::Semantic::Colors(_)
-                | gltf::Semantic::TexCoords(_) => {
+        let position_format = match position_format {
+            Some(position_format) => position_format,
+            None => {
+                return Err(Error::new(
+                    "The given primitive has no position attribute.".to_string(),
+                )
+                .into())
+            }
+        };
+
+        // Points and lines are shaded unlit, so they are never given normals
+        let flat_normals = match normal_format {
+            Some(_) => None,
+            None if !is_lit => None,
+            None => {
+                let positions = self.read_attribute::<3>(
+                    primitive,
+                    &gltf::Semantic::Positions,
+                    vertex_indices.as_deref(),
+                )?;
+                let flat_normals = PrimitiveGeometry::generate_flat_normals(&positions);
+
+                vertex_buffer_allocator.add_segment(
+                    gltf::Semantic::Normals,
+                    VertexBufferSegmentDataSource::Raw {
+                        data: bytemuck::cast_slice(&flat_normals).to_vec(),
+                    },
+                );
+                normal_format = Some(wgpu::VertexFormat::Float32x3);
+
+                Some(flat_normals)
+            }
+        };
+
+        let material = self.load_material(&primitive.material())?;
+
+        if needs_tangents && is_lit {
+            match self.generate_tangents(
+                primitive,
+                material.tex_coord_sets().normal,
+                vertex_indices.as_deref(),
+                flat_normals,
+            ) {
+                Ok(tangents) => {
                     vertex_buffer_allocator.add_segment(
-                        semantic,
-                        VertexBufferSegmentDataSource::Accessor {
-                            id: accessor.index(),
-                            length: (accessor.count() * accessor.size()) as u64,
+                        gltf::Semantic::Tangents,
+                        VertexBufferSegmentDataSource::Raw {
+                            data: bytemuck::cast_slice(&tangents).to_vec(),
                         },
                     );
+                    tangent_format = Some(wgpu::VertexFormat::Float32x4);
                 }
-                _ => {}
+                Err(error) => log::warn!(
+                    "Unable to generate tangents for the normal texture of {label_prefix}: {error}"
+                ),
             }
         }
 
@@ -274,10 +600,33 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
             self.gltf_loader,
         )?;
 
-        let index_buffer = match primitive.indices() {
-            Some(accessor) => {
-                let length = accessor.count() * accessor.size();
-
+        let index_buffer = match (primitive.indices(), &vertex_indices, &converted_indices) {
+            (_, None, Some(converted_indices)) => Some(Self::create_index_buffer(
+                &label_prefix,
+                bytemuck::cast_slice(converted_indices),
+                wgpu::IndexFormat::Uint32,
+                &self.state.device,
+                &self.state.queue,
+            )),
+            (Some(accessor), None, None)
+                if accessor.data_type() == gltf::accessor::DataType::U8 =>
+            {
+                // There is no 8-bit index format, so the indices are widened to 16 bits
+                let indices = self
+                    .read_indices(&accessor)?
+                    .into_iter()
+                    .map(|index| index as u16)
+                    .collect::<Vec<_>>();
+
+                Some(Self::create_index_buffer(
+                    &label_prefix,
+                    bytemuck::cast_slice(&indices),
+                    wgpu::IndexFormat::Uint16,
+                    &self.state.device,
+                    &self.state.queue,
+                ))
+            }
+            (Some(accessor), None, None) => {
                 let type_ = match accessor.data_type() {
                     gltf::accessor::DataType::U16 => wgpu::IndexFormat::Uint16,
                     gltf::accessor::DataType::U32 => wgpu::IndexFormat::Uint32,
@@ -288,30 +637,29 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
                     .into()),
                 };
 
-                let gpu_buffer = self.state.device.create_buffer(&wgpu::BufferDescriptor {
-                    label: Some(&format!("{label_prefix}_INDEX_BUFFER")),
-                    size: length as u64,
-                    usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
-                    mapped_at_creation: false,
-                });
-
                 let data = self
                     .gltf_loader
                     .load_bytes_from_accessor(accessor.index())?;
 
-                self.state.queue.write_buffer(&gpu_buffer, 0, data);
-                self.state.queue.submit([]);
-
-                Some(IndexBuffer { gpu_buffer, type_ })
+                Some(Self::create_index_buffer(
+                    &label_prefix,
+                    data,
+                    type_,
+                    &self.state.device,
+                    &self.state.queue,
+                ))
             }
-            None => None,
+            _ => None,
         };
 
-        let material = self.load_material(&primitive.material())?;
+        let bounding_box = primitive.bounding_box();
+        let centroid = cgmath::Point3::from(bounding_box.min).midpoint(bounding_box.max.into());
 
-        let count = match primitive.indices() {
-            Some(accessor) => accessor.count(),
-            None => match primitive.attributes().next() {
+        let count = match (primitive.indices(), &vertex_indices, &converted_indices) {
+            (_, Some(vertex_indices), _) => vertex_indices.len(),
+            (_, None, Some(converted_indices)) => converted_indices.len(),
+            (Some(accessor), None, None) => accessor.count(),
+            (None, None, None) => match primitive.attributes().next() {
                 Some((_, accessor)) => accessor.count(),
                 None => return Err(Error::new(String::from(
                     "Unable to determine the number of vertices to render for the given primitive.",
@@ -321,24 +669,245 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
         };
 
         let render_pipeline_config = RenderPipelineConfiguration {
-            has_normal,
-            has_tangent,
-            has_tex_coord_0,
-            has_tex_coord_1,
-            has_color_0,
+            position_format,
+            normal_format,
+            tangent_format,
+            tex_coord_formats: tex_coord_formats.into_iter().flatten().collect(),
+            color_formats: color_formats
+                .into_iter()
+                .flatten()
+                .zip(color_alphas)
+                .map(|(format, has_alpha)| VertexColorFormat { format, has_alpha })
+                .collect(),
+            skin_format: match (joints_format, weights_format) {
+                (Some(joints_format), Some(weights_format)) => Some(VertexSkinFormat {
+                    joints_format,
+                    weights_format,
+                }),
+                _ => None,
+            },
+            has_normal_texture: primitive.material().normal_texture().is_some(),
+            tex_coord_sets: material.tex_coord_sets(),
+            alpha_mode: material.alpha_mode(),
+            double_sided: primitive.material().double_sided(),
+            front_face: wgpu::FrontFace::Ccw,
             topology,
+            sample_count: self.state.sample_count,
         };
-        let render_pipeline = self.get_render_pipeline(&render_pipeline_config)?;
 
         Ok(Primitive {
             vertex_buffer,
             index_buffer,
             material,
             count,
-            render_pipeline,
+            centroid,
+            render_pipeline_config,
         })
     }
 
+    fn generate_tangents(
+        &mut self,
+        primitive: &gltf::Primitive,
+        tex_coord_set: u32,
+        vertex_indices: Option<&[u32]>,
+        flat_normals: Option<Vec<[f32; 3]>>,
+    ) -> Result<Vec<[f32; 4]>> {
+        let positions =
+            self.read_attribute::<3>(primitive, &gltf::Semantic::Positions, vertex_indices)?;
+        let normals = match flat_normals {
+            Some(flat_normals) => flat_normals,
+            None => {
+                self.read_attribute::<3>(primitive, &gltf::Semantic::Normals, vertex_indices)?
+            }
+        };
+        let tex_coords = self.read_attribute::<2>(
+            primitive,
+            &gltf::Semantic::TexCoords(tex_coord_set),
+            vertex_indices,
+        )?;
+
+        // The attributes have already been de-indexed, so each face corner matches a vertex of its own
+        PrimitiveGeometry::new(None, positions, normals, tex_coords)?.generate_tangents()
+    }
+
+    fn read_attribute<const N: usize>(
+        &mut self,
+        primitive: &gltf::Primitive,
+        semantic: &gltf::Semantic,
+        vertex_indices: Option<&[u32]>,
+    ) -> Result<Vec<[f32; N]>> {
+        let accessor = match primitive.get(semantic) {
+            Some(accessor) => accessor,
+            None => {
+                return Err(Error::new(format!(
+                    "The given primitive has no {:?} attribute.",
+                    semantic
+                ))
+                .into())
+            }
+        };
+
+        let data = self
+            .gltf_loader
+            .load_bytes_from_accessor(accessor.index())?;
+        let elements = PrimitiveGeometry::read_elements(data, &accessor)?;
+
+        match vertex_indices {
+            Some(vertex_indices) => PrimitiveGeometry::deindex(&elements, vertex_indices),
+            None => Ok(elements),
+        }
+    }
+
+    fn read_primitive_indices(&mut self, primitive: &gltf::Primitive) -> Result<Vec<u32>> {
+        match primitive.indices() {
+            Some(accessor) => self.read_indices(&accessor),
+            None => match primitive.get(&gltf::Semantic::Positions) {
+                Some(accessor) => Ok((0..accessor.count() as u32).collect()),
+                None => Err(Error::new(
+                    "The given primitive has no position attribute.".to_string(),
+                )
+                .into()),
+            },
+        }
+    }
+
+    fn create_index_buffer(
+        label_prefix: &str,
+        data: &[u8],
+        type_: wgpu::IndexFormat,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> IndexBuffer {
+        // Buffer writes have to be a multiple of 4 bytes, which an odd number of 16-bit indices is not
+        let mut padded_data = data.to_vec();
+        padded_data.resize(
+            data.len()
+                .next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT as usize),
+            0,
+        );
+
+        let gpu_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{label_prefix}_INDEX_BUFFER")),
+            size: padded_data.len() as u64,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        queue.write_buffer(&gpu_buffer, 0, &padded_data);
+        queue.submit([]);
+
+        IndexBuffer { gpu_buffer, type_ }
+    }
+
+    fn read_indices(&mut self, accessor: &gltf::Accessor) -> Result<Vec<u32>> {
+        let data = self
+            .gltf_loader
+            .load_bytes_from_accessor(accessor.index())?;
+
+        PrimitiveGeometry::read_indices(data, accessor.data_type())
+    }
+
+    fn get_attribute_set_count(
+        primitive: &gltf::Primitive,
+        semantic: fn(u32) -> gltf::Semantic,
+    ) -> usize {
+        (0..)
+            .take_while(|index| primitive.get(&semantic(*index)).is_some())
+            .count()
+    }
+
+    fn get_vertex_format(accessor: &gltf::Accessor) -> Result<wgpu::VertexFormat> {
+        use gltf::accessor::{DataType, Dimensions};
+
+        // 8-bit vectors and 16-bit vectors with three components are widened to four components,
+        // since vertex buffer strides have to be a multiple of 4 bytes
+        let format = match (
+            accessor.data_type(),
+            accessor.dimensions(),
+            accessor.normalized(),
+        ) {
+            (DataType::F32, Dimensions::Vec2, _) => wgpu::VertexFormat::Float32x2,
+            (DataType::F32, Dimensions::Vec3, _) => wgpu::VertexFormat::Float32x3,
+            (DataType::F32, Dimensions::Vec4, _) => wgpu::VertexFormat::Float32x4,
+            (DataType::I8, Dimensions::Vec2 | Dimensions::Vec3 | Dimensions::Vec4, true) => {
+                wgpu::VertexFormat::Snorm8x4
+            }
+            (DataType::I8, Dimensions::Vec2 | Dimensions::Vec3 | Dimensions::Vec4, false) => {
+                wgpu::VertexFormat::Sint8x4
+            }
+            (DataType::U8, Dimensions::Vec2 | Dimensions::Vec3 | Dimensions::Vec4, true) => {
+                wgpu::VertexFormat::Unorm8x4
+            }
+            (DataType::U8, Dimensions::Vec2 | Dimensions::Vec3 | Dimensions::Vec4, false) => {
+                wgpu::VertexFormat::Uint8x4
+            }
+            (DataType::I16, Dimensions::Vec2, true) => wgpu::VertexFormat::Snorm16x2,
+            (DataType::I16, Dimensions::Vec2, false) => wgpu::VertexFormat::Sint16x2,
+            (DataType::I16, Dimensions::Vec3 | Dimensions::Vec4, true) => {
+                wgpu::VertexFormat::Snorm16x4
+            }
+            (DataType::I16, Dimensions::Vec3 | Dimensions::Vec4, false) => {
+                wgpu::VertexFormat::Sint16x4
+            }
+            (DataType::U16, Dimensions::Vec2, true) => wgpu::VertexFormat::Unorm16x2,
+            (DataType::U16, Dimensions::Vec2, false) => wgpu::VertexFormat::Uint16x2,
+            (DataType::U16, Dimensions::Vec3 | Dimensions::Vec4, true) => {
+                wgpu::VertexFormat::Unorm16x4
+            }
+            (DataType::U16, Dimensions::Vec3 | Dimensions::Vec4, false) => {
+                wgpu::VertexFormat::Uint16x4
+            }
+            (data_type, dimensions, _) => {
+                return Err(Error::new(format!(
+                    "The given accessor uses a type that is not supported for vertex attributes: {:?} {:?}",
+                    data_type, dimensions
+                ))
+                .into())
+            }
+        };
+
+        Ok(format)
+    }
+
+    fn get_vertex_data_source(
+        &mut self,
+        accessor: &gltf::Accessor,
+        vertex_format: wgpu::VertexFormat,
+        vertex_indices: Option<&[u32]>,
+    ) -> Result<VertexBufferSegmentDataSource> {
+        let element_size = accessor.size();
+        let padded_element_size = vertex_format.size() as usize;
+
+        if element_size == padded_element_size && vertex_indices.is_none() {
+            return Ok(VertexBufferSegmentDataSource::Accessor {
+                id: accessor.index(),
+                length: (accessor.count() * element_size) as u64,
+            });
+        }
+
+        let data = self
+            .gltf_loader
+            .load_bytes_from_accessor(accessor.index())?;
+
+        let mut padded_data = Vec::<u8>::with_capacity(accessor.count() * padded_element_size);
+        for element in data.chunks_exact(element_size) {
+            padded_data.extend_from_slice(element);
+            padded_data.resize(padded_data.len() + padded_element_size - element_size, 0);
+        }
+
+        if let Some(vertex_indices) = vertex_indices {
+            padded_data = PrimitiveGeometry::deindex(
+                &padded_data
+                    .chunks_exact(padded_element_size)
+                    .collect::<Vec<_>>(),
+                vertex_indices,
+            )?
+            .concat();
+        }
+
+        Ok(VertexBufferSegmentDataSource::Raw { data: padded_data })
+    }
+
     fn load_material(&mut self, material: &gltf::Material) -> Result<std::rc::Rc<Material>> {
         let material_log_name = format!(
             "{} - [{}]",
@@ -376,21 +945,80 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
                 mapped_at_creation: false,
             });
 
-        let base_color_texture = match material.pbr_metallic_roughness().base_color_texture() {
-            Some(texture_info) => {
-                self.load_texture(&texture_info, wgpu::TextureFormat::Rgba8UnormSrgb)?
-            }
-            None => self.load_default_texture(),
+        let (base_color_texture, base_color_texture_transform) = match material
+            .pbr_metallic_roughness()
+            .base_color_texture()
+        {
+            Some(texture_info) => (
+                self.load_texture(&texture_info.texture(), wgpu::TextureFormat::Rgba8UnormSrgb)?,
+                Self::load_texture_transform(
+                    texture_info.tex_coord(),
+                    texture_info.extension_value("KHR_texture_transform"),
+                )?,
+            ),
+            None => (self.load_default_texture(), Default::default()),
         };
 
-        let metallic_roughness_texture = match material
+        let (metallic_roughness_texture, metallic_roughness_texture_transform) = match material
             .pbr_metallic_roughness()
             .metallic_roughness_texture()
         {
-            Some(texture_info) => {
-                self.load_texture(&texture_info, wgpu::TextureFormat::Rgba8Unorm)?
-            }
-            None => self.load_default_texture(),
+            Some(texture_info) => (
+                self.load_texture(&texture_info.texture(), wgpu::TextureFormat::Rgba8Unorm)?,
+                Self::load_texture_transform(
+                    texture_info.tex_coord(),
+                    texture_info.extension_value("KHR_texture_transform"),
+                )?,
+            ),
+            None => (self.load_default_texture(), Default::default()),
+        };
+
+        let (normal_texture, normal_scale, normal_texture_transform) =
+            match material.normal_texture() {
+                Some(normal_texture) => (
+                    self.load_texture(&normal_texture.texture(), wgpu::TextureFormat::Rgba8Unorm)?,
+                    normal_texture.scale(),
+                    Self::load_texture_transform(
+                        normal_texture.tex_coord(),
+                        normal_texture.extension_value("KHR_texture_transform"),
+                    )?,
+                ),
+                None => (self.load_default_texture(), 1.0, Default::default()),
+            };
+
+        let (occlusion_texture, occlusion_strength, occlusion_texture_transform) =
+            match material.occlusion_texture() {
+                Some(occlusion_texture) => (
+                    self.load_texture(
+                        &occlusion_texture.texture(),
+                        wgpu::TextureFormat::Rgba8Unorm,
+                    )?,
+                    occlusion_texture.strength(),
+                    Self::load_texture_transform(
+                        occlusion_texture.tex_coord(),
+                        occlusion_texture.extension_value("KHR_texture_transform"),
+                    )?,
+                ),
+                None => (self.load_default_texture(), 1.0, Default::default()),
+            };
+
+        let (emissive_texture, emissive_texture_transform) = match material.emissive_texture() {
+            Some(texture_info) => (
+                self.load_texture(&texture_info.texture(), wgpu::TextureFormat::Rgba8UnormSrgb)?,
+                Self::load_texture_transform(
+                    texture_info.tex_coord(),
+                    texture_info.extension_value("KHR_texture_transform"),
+                )?,
+            ),
+            None => (self.load_default_texture(), Default::default()),
+        };
+
+        let texture_transforms = MaterialTextureTransforms {
+            base_color: base_color_texture_transform,
+            metallic_roughness: metallic_roughness_texture_transform,
+            normal: normal_texture_transform,
+            occlusion: occlusion_texture_transform,
+            emissive: emissive_texture_transform,
         };
 
         let gpu_bind_group = self
@@ -428,15 +1056,63 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
                             &metallic_roughness_texture.sampler.gpu_sampler,
                         ),
                     },
+                    wgpu::BindGroupEntry {
+                        binding: 5,
+                        resource: wgpu::BindingResource::TextureView(
+                            &normal_texture.gpu_texture_view,
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 6,
+                        resource: wgpu::BindingResource::Sampler(
+                            &normal_texture.sampler.gpu_sampler,
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 7,
+                        resource: wgpu::BindingResource::TextureView(
+                            &occlusion_texture.gpu_texture_view,
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 8,
+                        resource: wgpu::BindingResource::Sampler(
+                            &occlusion_texture.sampler.gpu_sampler,
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 9,
+                        resource: wgpu::BindingResource::TextureView(
+                            &emissive_texture.gpu_texture_view,
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 10,
+                        resource: wgpu::BindingResource::Sampler(
+                            &emissive_texture.sampler.gpu_sampler,
+                        ),
+                    },
                 ],
             });
 
         let loaded_material = std::rc::Rc::new(Material::new(
-            material.pbr_metallic_roughness().base_color_factor(),
-            base_color_texture,
-            material.pbr_metallic_roughness().metallic_factor(),
-            material.pbr_metallic_roughness().roughness_factor(),
-            metallic_roughness_texture,
+            MaterialParameters {
+                base_color_factor: material.pbr_metallic_roughness().base_color_factor(),
+                base_color_texture,
+                metallic_factor: material.pbr_metallic_roughness().metallic_factor(),
+                roughness_factor: material.pbr_metallic_roughness().roughness_factor(),
+                metallic_roughness_texture,
+                normal_texture,
+                normal_scale,
+                occlusion_texture,
+                occlusion_strength,
+                emissive_factor: material.emissive_factor(),
+                emissive_texture,
+                emissive_strength: material.emissive_strength().unwrap_or(1.0),
+                alpha_mode: AlphaMode::from_gltf(material.alpha_mode()),
+                alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+                texture_transforms,
+            },
             gpu_metallic_roughness_uniform_buffer,
             gpu_bind_group,
             &self.state.queue,
@@ -449,13 +1125,36 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
         Ok(loaded_material)
     }
 
+    fn load_texture_transform(
+        tex_coord: u32,
+        extension_value: Option<&gltf::json::Value>,
+    ) -> Result<TextureTransform> {
+        let extension = match extension_value {
+            Some(extension_value) => {
+                serde_json::from_value::<TextureTransformExtension>(extension_value.clone())?
+            }
+            None => {
+                return Ok(TextureTransform {
+                    tex_coord,
+                    ..Default::default()
+                })
+            }
+        };
+
+        Ok(TextureTransform {
+            offset: extension.offset,
+            rotation: extension.rotation,
+            scale: extension.scale,
+            // The extension may override the texture coordinate set of the texture
+            tex_coord: extension.tex_coord.unwrap_or(tex_coord),
+        })
+    }
+
     fn load_texture(
         &mut self,
-        texture_info: &gltf::texture::Info,
+        texture: &gltf::Texture,
         format: wgpu::TextureFormat,
     ) -> Result<std::rc::Rc<Texture2DPackage>> {
-        let texture = texture_info.texture();
-
         let texture_log_name = format!(
             "{} - [{}]",
             texture.name().unwrap_or("<UNNAMED>"),
@@ -486,11 +1185,13 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
         let gpu_texture = self.state.device.create_texture(&wgpu::TextureDescriptor {
             label: Some(&texture_label),
             size: image_size,
-            mip_level_count: 1,
+            mip_level_count: image_size.max_mips(wgpu::TextureDimension::D2),
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
             view_formats: &[],
         });
 
@@ -510,6 +1211,10 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
             image_size,
         );
 
+        self.state
+            .mipmap_generator
+            .generate_mipmaps(&texture_label, &gpu_texture)?;
+
         let gpu_texture_view = gpu_texture.create_view(&wgpu::TextureViewDescriptor::default());
         let sampler = self.load_sampler(&texture.sampler())?;
 
@@ -675,6 +1380,28 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
             None => wgpu::FilterMode::Linear,
         };
 
+        // Minification filters without a mipmap mode only ever sample the base level
+        let lod_max_clamp = match sampler.min_filter() {
+            Some(gltf::texture::MinFilter::Linear) | Some(gltf::texture::MinFilter::Nearest) => 0.0,
+            _ => 32.0,
+        };
+
+        // Anisotropic filtering is only valid when every filter is linear
+        let anisotropy_clamp = if self
+            .state
+            .adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING)
+            && mag_filter == wgpu::FilterMode::Linear
+            && min_filter == wgpu::FilterMode::Linear
+            && mipmap_filter == wgpu::FilterMode::Linear
+        {
+            16
+        } else {
+            1
+        };
+
         let loaded_sampler = std::rc::Rc::new(Sampler {
             gpu_sampler: self.state.device.create_sampler(&wgpu::SamplerDescriptor {
                 address_mode_u,
@@ -683,6 +1410,8 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
                 mag_filter,
                 min_filter,
                 mipmap_filter,
+                lod_max_clamp,
+                anisotropy_clamp,
                 ..Default::default()
             }),
         });
@@ -725,29 +1454,68 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
             render_pipeline_config
         );
 
+        // Skinned primitives additionally bind the joint matrices of their skin
+        let mut bind_group_layouts = vec![
+            &self.state.view_environment_bind_group_layout,
+            &self.state.primitive_instance_bind_group_layout,
+            &self.state.material_bind_group_layout,
+        ];
+        if render_pipeline_config.skin_format.is_some() {
+            bind_group_layouts.push(&self.state.skin_bind_group_layout);
+        }
+
         let render_pipeline = std::rc::Rc::new(RenderPipeline::from_config(
-            *render_pipeline_config,
+            render_pipeline_config.clone(),
             format!(
                 "RENDER_PIPELINE_{}",
                 self.storage.render_pipeline_registry.len()
             ),
             &self.state.device,
-            &[
-                &self.state.view_environment_bind_group_layout,
-                &self.state.primitive_instance_bind_group_layout,
-                &self.state.material_bind_group_layout,
-            ],
+            &bind_group_layouts,
             &shader_module_package.vertex_shader_module,
             &shader_module_package.fragment_shader_module,
-            self.state.surface_config.format,
+            self.state.target.format(),
         ));
         self.storage
             .render_pipeline_registry
-            .insert(*render_pipeline_config, render_pipeline.clone());
+            .insert(render_pipeline_config.clone(), render_pipeline.clone());
 
         Ok(render_pipeline)
     }
 
+    fn get_instance_render_pipeline(
+        &mut self,
+        primitive: &Primitive,
+        mirrored: bool,
+        skin_orientation: Option<SkinOrientation>,
+    ) -> Result<std::rc::Rc<RenderPipeline>> {
+        let config = &primitive.render_pipeline_config;
+
+        // Primitives with joints and weights are only skinned when their node references a skin
+        let (skin_format, mirrored, double_sided) = match skin_orientation {
+            Some(skin_orientation) => (
+                config.skin_format,
+                skin_orientation == SkinOrientation::Mirrored,
+                // Faces of a mixed skin disagree on their winding order, so none of them may be culled
+                config.double_sided || skin_orientation == SkinOrientation::Mixed,
+            ),
+            None => (None, mirrored, config.double_sided),
+        };
+
+        // Skinned faces without culling have normals that flip along with their winding order instead
+        let front_face = match mirrored && !(skin_format.is_some() && double_sided) {
+            true => wgpu::FrontFace::Cw,
+            false => config.front_face,
+        };
+
+        self.get_render_pipeline(&RenderPipelineConfiguration {
+            front_face,
+            skin_format,
+            double_sided,
+            ..config.clone()
+        })
+    }
+
     fn get_shader_module_package(
         &mut self,
         shader_template_config: &ShaderTemplateConfiguration,
@@ -774,9 +1542,10 @@ impl<'a, T: GltfLoader> SceneLoader<'a, T> {
             Some(shader_template_config),
         )?);
 
-        self.storage
-            .shader_module_package_registry
-            .insert(*shader_template_config, shader_module_package.clone());
+        self.storage.shader_module_package_registry.insert(
+            shader_template_config.clone(),
+            shader_module_package.clone(),
+        );
 
         Ok(shader_module_package)
     }
@@ -1,5 +1,6 @@
 use anyhow::Result;
 
+use crate::render::material::{AlphaMode, MaterialTexCoordSets};
 use crate::render::pipeline::RenderPipelineConfiguration;
 
 pub struct ShaderModulePackage {
@@ -66,29 +67,109 @@ impl ShaderModulePackage {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, serde::Serialize)]
 pub struct ShaderTemplateConfiguration {
+    pub position_type: ShaderScalarType,
     pub has_normal: bool,
     pub has_tangent: bool,
-    pub has_tex_coord_0: bool,
-    pub tex_coord_0_location: u32,
-    pub has_tex_coord_1: bool,
-    pub tex_coord_1_location: u32,
-    pub has_color_0: bool,
-    pub color_0_location: u32,
+    pub tex_coords: Vec<ShaderTexCoordAttribute>,
+    pub colors: Vec<ShaderColorAttribute>,
+    pub skin: Option<ShaderSkinAttributes>,
+    pub has_normal_texture: bool,
+    pub tex_coord_sets: MaterialTexCoordSets,
+    pub material_tex_coord_set_count: u32,
+    pub alpha_mode: AlphaMode,
+    pub double_sided: bool,
+    pub unlit: bool,
 }
 
 impl ShaderTemplateConfiguration {
     pub fn from_render_pipeline_config(config: &RenderPipelineConfiguration) -> Self {
         Self {
-            has_normal: config.has_normal,
-            has_tangent: config.has_tangent,
-            has_tex_coord_0: config.has_tex_coord_0,
-            tex_coord_0_location: config.get_tex_coord_0_location(),
-            has_tex_coord_1: config.has_tex_coord_1,
-            tex_coord_1_location: config.get_tex_coord_1_location(),
-            has_color_0: config.has_color_0,
-            color_0_location: config.get_color_0_location(),
+            position_type: ShaderScalarType::from_vertex_format(config.position_format),
+            has_normal: config.normal_format.is_some(),
+            has_tangent: config.tangent_format.is_some(),
+            tex_coords: config
+                .tex_coord_formats
+                .iter()
+                .enumerate()
+                .map(|(index, format)| ShaderTexCoordAttribute {
+                    location: config.get_tex_coord_location(index),
+                    type_: ShaderScalarType::from_vertex_format(*format),
+                })
+                .collect(),
+            colors: config
+                .color_formats
+                .iter()
+                .enumerate()
+                .map(|(index, format)| ShaderColorAttribute {
+                    location: config.get_color_location(index),
+                    has_alpha: format.has_alpha,
+                })
+                .collect(),
+            skin: config.skin_format.map(|_| ShaderSkinAttributes {
+                joints_location: config.get_joints_location(),
+                weights_location: config.get_weights_location(),
+            }),
+            has_normal_texture: config.has_normal_texture,
+            tex_coord_sets: config.tex_coord_sets,
+            material_tex_coord_set_count: config.tex_coord_sets.max() + 1,
+            alpha_mode: config.alpha_mode,
+            double_sided: config.double_sided,
+            unlit: !config.is_lit(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize)]
+pub struct ShaderTexCoordAttribute {
+    pub location: u32,
+    #[serde(rename = "type")]
+    pub type_: ShaderScalarType,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize)]
+pub struct ShaderColorAttribute {
+    pub location: u32,
+    pub has_alpha: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize)]
+pub struct ShaderSkinAttributes {
+    pub joints_location: u32,
+    pub weights_location: u32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize)]
+pub enum ShaderScalarType {
+    #[serde(rename = "f32")]
+    F32,
+    #[serde(rename = "u32")]
+    U32,
+    #[serde(rename = "i32")]
+    I32,
+}
+
+impl ShaderScalarType {
+    pub fn from_vertex_format(format: wgpu::VertexFormat) -> Self {
+        match format {
+            wgpu::VertexFormat::Uint8x2
+            | wgpu::VertexFormat::Uint8x4
+            | wgpu::VertexFormat::Uint16x2
+            | wgpu::VertexFormat::Uint16x4
+            | wgpu::VertexFormat::Uint32
+            | wgpu::VertexFormat::Uint32x2
+            | wgpu::VertexFormat::Uint32x3
+            | wgpu::VertexFormat::Uint32x4 => ShaderScalarType::U32,
+            wgpu::VertexFormat::Sint8x2
+            | wgpu::VertexFormat::Sint8x4
+            | wgpu::VertexFormat::Sint16x2
+            | wgpu::VertexFormat::Sint16x4
+            | wgpu::VertexFormat::Sint32
+            | wgpu::VertexFormat::Sint32x2
+            | wgpu::VertexFormat::Sint32x3
+            | wgpu::VertexFormat::Sint32x4 => ShaderScalarType::I32,
+            _ => ShaderScalarType::F32,
         }
     }
 }
@@ -0,0 +1,206 @@
+use anyhow::Result;
+use cgmath::{EuclideanSpace, SquareMatrix};
+
+use crate::error::Error;
+
+// The joint matrices of a skin are bound as a fixed-size uniform array, which also fits within WebGL2 limits
+pub const MAX_JOINT_COUNT: usize = 256;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SkinOrientation {
+    Preserved,
+    Mirrored,
+    // Joints that disagree on mirroring invert the winding order of only some of the faces
+    Mixed,
+}
+
+pub struct Skin {
+    pub joint_matrices: Vec<cgmath::Matrix4<f32>>,
+    pub orientation: SkinOrientation,
+    #[allow(dead_code)]
+    pub gpu_joint_uniform_buffer: wgpu::Buffer,
+    pub gpu_bind_group: wgpu::BindGroup,
+}
+
+impl Skin {
+    pub fn from_device(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        name: &str,
+        joint_matrices: Vec<cgmath::Matrix4<f32>>,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Result<Self> {
+        if joint_matrices.len() > MAX_JOINT_COUNT {
+            return Err(Error::new(format!(
+                "The given skin has {} joints, which exceeds the maximum of {MAX_JOINT_COUNT}.",
+                joint_matrices.len()
+            ))
+            .into());
+        }
+
+        let mut joint_uniform = joint_matrices
+            .iter()
+            .map(|joint_matrix| (*joint_matrix).into())
+            .collect::<Vec<[[f32; 4]; 4]>>();
+        joint_uniform.resize(MAX_JOINT_COUNT, [[0.0; 4]; 4]);
+
+        let gpu_joint_uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{name}_JOINT_UNIFORM_BUFFER")),
+            size: std::mem::size_of_val(joint_uniform.as_slice()) as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        queue.write_buffer(
+            &gpu_joint_uniform_buffer,
+            0,
+            bytemuck::cast_slice(&joint_uniform),
+        );
+
+        let gpu_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{name}_BIND_GROUP")),
+            layout: bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: gpu_joint_uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        Ok(Self {
+            orientation: Self::get_orientation(&joint_matrices),
+            joint_matrices,
+            gpu_joint_uniform_buffer,
+            gpu_bind_group,
+        })
+    }
+
+    pub fn get_joint_matrices(
+        global_transform_matrices: &[cgmath::Matrix4<f32>],
+        inverse_bind_matrices: &[cgmath::Matrix4<f32>],
+    ) -> Vec<cgmath::Matrix4<f32>> {
+        global_transform_matrices
+            .iter()
+            .zip(inverse_bind_matrices)
+            .map(|(global_transform_matrix, inverse_bind_matrix)| {
+                global_transform_matrix * inverse_bind_matrix
+            })
+            .collect()
+    }
+
+    pub fn skin_position(
+        joint_matrices: &[cgmath::Matrix4<f32>],
+        position: [f32; 3],
+        joints: [f32; 4],
+        weights: [f32; 4],
+    ) -> Result<cgmath::Point3<f32>> {
+        let mut skin_matrix = cgmath::Matrix4::from_scale(0.0);
+        for (joint, weight) in joints.into_iter().zip(weights) {
+            match joint_matrices.get(joint as usize) {
+                Some(joint_matrix) => skin_matrix += joint_matrix * weight,
+                None => {
+                    return Err(Error::new(format!(
+                        "The given vertex references a joint that is out of range: {joint}"
+                    ))
+                    .into())
+                }
+            }
+        }
+
+        Ok(cgmath::Point3::from_vec(
+            (skin_matrix * cgmath::Point3::from(position).to_homogeneous()).truncate(),
+        ))
+    }
+
+    fn get_orientation(joint_matrices: &[cgmath::Matrix4<f32>]) -> SkinOrientation {
+        let mirrored_count = joint_matrices
+            .iter()
+            .filter(|joint_matrix| joint_matrix.determinant() < 0.0)
+            .count();
+
+        match mirrored_count {
+            0 => SkinOrientation::Preserved,
+            count if count == joint_matrices.len() => SkinOrientation::Mirrored,
+            _ => SkinOrientation::Mixed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::InnerSpace;
+
+    use super::{Skin, SkinOrientation};
+
+    #[test]
+    fn get_joint_matrices_applies_inverse_bind_matrices_first() {
+        let global_transform_matrices = [
+            cgmath::Matrix4::from_translation(cgmath::Vector3::new(0.0, 2.0, 0.0)),
+            cgmath::Matrix4::from_angle_z(cgmath::Deg(90.0)),
+        ];
+        let inverse_bind_matrices = [
+            cgmath::Matrix4::from_translation(cgmath::Vector3::new(0.0, -1.0, 0.0)),
+            cgmath::Matrix4::from_translation(cgmath::Vector3::new(-1.0, 0.0, 0.0)),
+        ];
+
+        let joint_matrices =
+            Skin::get_joint_matrices(&global_transform_matrices, &inverse_bind_matrices);
+
+        assert_eq!(joint_matrices.len(), 2);
+        assert_eq!(
+            joint_matrices[0],
+            cgmath::Matrix4::from_translation(cgmath::Vector3::new(0.0, 1.0, 0.0)),
+        );
+        // The translation of the inverse bind matrix is rotated by the joint
+        let translation = joint_matrices[1].w.truncate();
+        assert!((translation - cgmath::Vector3::new(0.0, -1.0, 0.0)).magnitude2() < 1e-10);
+    }
+
+    #[test]
+    fn skin_position_blends_joint_matrices_by_weight() {
+        let joint_matrices = [
+            cgmath::Matrix4::from_translation(cgmath::Vector3::new(2.0, 0.0, 0.0)),
+            cgmath::Matrix4::from_translation(cgmath::Vector3::new(0.0, 4.0, 0.0)),
+        ];
+
+        assert_eq!(
+            Skin::skin_position(
+                &joint_matrices,
+                [1.0, 1.0, 1.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.5, 0.5, 0.0, 0.0],
+            )
+            .unwrap(),
+            cgmath::Point3::new(2.0, 3.0, 1.0),
+        );
+        assert!(Skin::skin_position(
+            &joint_matrices,
+            [0.0; 3],
+            [2.0, 0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn get_orientation_detects_joints_that_disagree_on_mirroring() {
+        let mirror = cgmath::Matrix4::from_nonuniform_scale(-1.0, 1.0, 1.0);
+        let identity = cgmath::Matrix4::from_scale(1.0);
+
+        assert_eq!(
+            Skin::get_orientation(&[identity, identity]),
+            SkinOrientation::Preserved
+        );
+        assert_eq!(
+            Skin::get_orientation(&[mirror, mirror]),
+            SkinOrientation::Mirrored
+        );
+        assert_eq!(
+            Skin::get_orientation(&[mirror, mirror, identity]),
+            SkinOrientation::Mixed
+        );
+        assert_eq!(
+            Skin::get_orientation(&[mirror, identity]),
+            SkinOrientation::Mixed
+        );
+    }
+}
@@ -19,6 +19,7 @@ impl SkyboxRenderer {
         device: std::rc::Rc<wgpu::Device>,
         queue: std::rc::Rc<wgpu::Queue>,
         format: wgpu::TextureFormat,
+        sample_count: u32,
         tera: &tera::Tera,
     ) -> Result<Self> {
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -116,7 +117,7 @@ impl SkyboxRenderer {
                 bias: wgpu::DepthBiasState::default(),
             }),
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -8,74 +8,48 @@ use crate::render::cubemap::CubeMap;
 use crate::render::equirectangular::EquirectangularToCubeMapRenderer;
 use crate::render::ibl::IblEnvironment;
 use crate::render::lut::GgxLut;
+use crate::render::mipmap::MipmapGenerator;
 use crate::render::skybox::SkyboxRenderer;
-use crate::render::texture::DepthTexture2DPackage;
+use crate::render::target::RenderTarget;
+use crate::render::texture::{ColorTexture2DPackage, DepthTexture2DPackage};
 use crate::render::view::ViewEnvironment;
 
 pub struct RenderSystemState {
     #[allow(dead_code)]
     pub instance: wgpu::Instance,
-    pub surface: wgpu::Surface<'static>,
-    pub surface_config: wgpu::SurfaceConfiguration,
-    #[allow(dead_code)]
+    pub target: RenderTarget,
     pub adapter: wgpu::Adapter,
     pub device: std::rc::Rc<wgpu::Device>,
     pub queue: std::rc::Rc<wgpu::Queue>,
     pub view_environment_bind_group_layout: std::rc::Rc<wgpu::BindGroupLayout>,
     pub primitive_instance_bind_group_layout: wgpu::BindGroupLayout,
+    pub skin_bind_group_layout: wgpu::BindGroupLayout,
     pub material_bind_group_layout: wgpu::BindGroupLayout,
+    pub sample_count: u32,
+    pub multisampled_color_texture: Option<ColorTexture2DPackage>,
     pub depth_texture: DepthTexture2DPackage,
     pub tera: tera::Tera,
     pub equirectangular_to_cubemap_renderer: EquirectangularToCubeMapRenderer,
+    pub mipmap_generator: MipmapGenerator,
     pub skybox_renderer: SkyboxRenderer,
     pub view_environment: ViewEnvironment,
     pub view_dimensions: winit::dpi::PhysicalSize<u32>,
 }
 
 impl RenderSystemState {
-    pub async fn from_window(window: std::sync::Arc<winit::window::Window>) -> Result<Self> {
+    pub async fn from_window(
+        window: std::sync::Arc<winit::window::Window>,
+        sample_count: u32,
+    ) -> Result<Self> {
         let view_dimensions = window.inner_size();
 
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            #[cfg(target_arch = "wasm32")]
-            backends: wgpu::Backends::GL,
-            #[cfg(not(target_arch = "wasm32"))]
-            backends: wgpu::Backends::PRIMARY,
-            ..Default::default()
-        });
+        let instance = RenderSystemState::create_instance(wgpu::Backends::PRIMARY);
 
         let surface = instance.create_surface(window)?;
 
-        let adapter = match instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-        {
-            Some(adapter) => adapter,
-            None => return Err(Error::new(String::from("Failed to retrieve adapter.")).into()),
-        };
+        let adapter = RenderSystemState::request_adapter(&instance, Some(&surface), false).await?;
 
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
-                    required_limits: if cfg!(target_arch = "wasm32") {
-                        wgpu::Limits::downlevel_webgl2_defaults()
-                    } else {
-                        wgpu::Limits::default()
-                    },
-                    label: None,
-                    ..Default::default()
-                },
-                None,
-            )
-            .await?;
-
-        let device = std::rc::Rc::new(device);
-        let queue = std::rc::Rc::new(queue);
+        let (device, queue) = RenderSystemState::request_device(&adapter).await?;
 
         let surface_caps = surface.get_capabilities(&adapter);
 
@@ -99,6 +73,113 @@ impl RenderSystemState {
 
         surface.configure(&device, &surface_config);
 
+        RenderSystemState::from_device(
+            instance,
+            RenderTarget::Surface {
+                surface,
+                config: surface_config,
+            },
+            adapter,
+            device,
+            queue,
+            view_dimensions,
+            sample_count,
+        )
+    }
+
+    pub async fn from_dimensions(
+        view_dimensions: winit::dpi::PhysicalSize<u32>,
+        force_fallback_adapter: bool,
+        sample_count: u32,
+    ) -> Result<Self> {
+        // Headless machines often only expose a software adapter through a secondary backend
+        let instance = RenderSystemState::create_instance(wgpu::Backends::all());
+
+        let adapter =
+            RenderSystemState::request_adapter(&instance, None, force_fallback_adapter).await?;
+
+        let (device, queue) = RenderSystemState::request_device(&adapter).await?;
+
+        let target = RenderTarget::from_dimensions(&device, view_dimensions);
+
+        RenderSystemState::from_device(
+            instance,
+            target,
+            adapter,
+            device,
+            queue,
+            view_dimensions,
+            sample_count,
+        )
+    }
+
+    fn create_instance(native_backends: wgpu::Backends) -> wgpu::Instance {
+        wgpu::Instance::new(wgpu::InstanceDescriptor {
+            #[cfg(target_arch = "wasm32")]
+            backends: wgpu::Backends::GL,
+            #[cfg(not(target_arch = "wasm32"))]
+            backends: wgpu::util::backend_bits_from_env().unwrap_or(native_backends),
+            ..Default::default()
+        })
+    }
+
+    async fn request_adapter(
+        instance: &wgpu::Instance,
+        compatible_surface: Option<&wgpu::Surface<'static>>,
+        force_fallback_adapter: bool,
+    ) -> Result<wgpu::Adapter> {
+        match instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface,
+                force_fallback_adapter,
+            })
+            .await
+        {
+            Some(adapter) => Ok(adapter),
+            None => Err(Error::new(String::from("Failed to retrieve adapter.")).into()),
+        }
+    }
+
+    async fn request_device(adapter: &wgpu::Adapter) -> Result<(wgpu::Device, wgpu::Queue)> {
+        Ok(adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    // Without this feature only the sample counts guaranteed by WebGPU can be used
+                    required_features: adapter.features()
+                        & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+                    required_limits: if cfg!(target_arch = "wasm32") {
+                        wgpu::Limits::downlevel_webgl2_defaults()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?)
+    }
+
+    fn from_device(
+        instance: wgpu::Instance,
+        target: RenderTarget,
+        adapter: wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        view_dimensions: winit::dpi::PhysicalSize<u32>,
+        sample_count: u32,
+    ) -> Result<Self> {
+        let sample_count = RenderSystemState::get_supported_sample_count(
+            &adapter,
+            &device,
+            target.format(),
+            sample_count,
+        );
+
+        let device = std::rc::Rc::new(device);
+        let queue = std::rc::Rc::new(queue);
+
         let view_environment_bind_group_layout = std::rc::Rc::new(device.create_bind_group_layout(
             &wgpu::BindGroupLayoutDescriptor {
                 label: Some("VIEW_ENVIRONMENT_BIND_GROUP_LAYOUT"),
@@ -187,6 +268,21 @@ impl RenderSystemState {
                 }],
             });
 
+        let skin_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("SKIN_BIND_GROUP_LAYOUT"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
         let material_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("MATERIAL_BIND_GROUP_LAYOUT"),
@@ -233,25 +329,87 @@ impl RenderSystemState {
                         ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                         count: None,
                     },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 5,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 6,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 7,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 8,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 9,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 10,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
                 ],
             });
 
-        let depth_texture = RenderSystemState::create_depth_texture(
+        let view_size = wgpu::Extent3d {
+            width: view_dimensions.width.max(1),
+            height: view_dimensions.height.max(1),
+            depth_or_array_layers: 1,
+        };
+
+        let multisampled_color_texture = RenderSystemState::create_multisampled_color_texture(
             &device,
-            wgpu::Extent3d {
-                width: view_dimensions.width.max(1),
-                height: view_dimensions.height.max(1),
-                depth_or_array_layers: 1,
-            },
+            view_size,
+            target.format(),
+            sample_count,
         );
 
+        let depth_texture =
+            RenderSystemState::create_depth_texture(&device, view_size, sample_count);
+
         let tera = tera::Tera::new("shaders/**/*")?;
 
         let equirectangular_to_cubemap_renderer =
             EquirectangularToCubeMapRenderer::from_device(device.clone(), queue.clone(), &tera)?;
 
-        let skybox_renderer =
-            SkyboxRenderer::from_device(device.clone(), queue.clone(), surface_format, &tera)?;
+        let mipmap_generator = MipmapGenerator::from_device(device.clone(), queue.clone(), &tera)?;
+
+        let skybox_renderer = SkyboxRenderer::from_device(
+            device.clone(),
+            queue.clone(),
+            target.format(),
+            sample_count,
+            &tera,
+        )?;
 
         let skybox = skybox_renderer.create_default_skybox("IBL_ENVIRONMENT_SKYBOX_CUBEMAP")?;
 
@@ -290,7 +448,7 @@ impl RenderSystemState {
         let view_environment = ViewEnvironment::from_device(
             device.clone(),
             queue.clone(),
-            surface_config.width as f32 / surface_config.height as f32,
+            view_dimensions.width as f32 / view_dimensions.height as f32,
             user_camera,
             ibl_environment,
             view_environment_bind_group_layout.clone(),
@@ -298,17 +456,20 @@ impl RenderSystemState {
 
         Ok(Self {
             instance,
-            surface,
-            surface_config,
+            target,
             adapter,
             device,
             queue,
             view_environment_bind_group_layout,
             primitive_instance_bind_group_layout,
+            skin_bind_group_layout,
             material_bind_group_layout,
+            sample_count,
+            multisampled_color_texture,
             depth_texture,
             tera,
             equirectangular_to_cubemap_renderer,
+            mipmap_generator,
             skybox_renderer,
             view_environment,
             view_dimensions,
@@ -317,30 +478,97 @@ impl RenderSystemState {
 
     pub fn set_view_dimensions(&mut self, view_dimensions: winit::dpi::PhysicalSize<u32>) {
         self.view_dimensions = view_dimensions;
-        self.surface_config.width = view_dimensions.width;
-        self.surface_config.height = view_dimensions.height;
-        self.surface.configure(&self.device, &self.surface_config);
-        self.depth_texture = RenderSystemState::create_depth_texture(
+        self.target
+            .set_view_dimensions(&self.device, view_dimensions);
+        let view_size = wgpu::Extent3d {
+            width: view_dimensions.width.max(1),
+            height: view_dimensions.height.max(1),
+            depth_or_array_layers: 1,
+        };
+        self.multisampled_color_texture = RenderSystemState::create_multisampled_color_texture(
             &self.device,
-            wgpu::Extent3d {
-                width: view_dimensions.width.max(1),
-                height: view_dimensions.height.max(1),
-                depth_or_array_layers: 1,
-            },
+            view_size,
+            self.target.format(),
+            self.sample_count,
         );
+        self.depth_texture =
+            RenderSystemState::create_depth_texture(&self.device, view_size, self.sample_count);
         self.view_environment
             .set_aspect_ratio(view_dimensions.width as f32 / view_dimensions.height as f32);
     }
 
-    fn create_depth_texture(device: &wgpu::Device, size: wgpu::Extent3d) -> DepthTexture2DPackage {
+    fn get_supported_sample_count(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> u32 {
+        let is_supported = [format, wgpu::TextureFormat::Depth32Float]
+            .into_iter()
+            .all(|format| {
+                let format_features = if device
+                    .features()
+                    .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+                {
+                    adapter.get_texture_format_features(format)
+                } else {
+                    format.guaranteed_format_features(device.features())
+                };
+
+                format_features.flags.sample_count_supported(sample_count)
+            });
+
+        if !is_supported {
+            log::warn!(
+                "The sample count {sample_count} is not supported by the adapter, multisampling will be disabled."
+            );
+            return 1;
+        }
+
+        sample_count
+    }
+
+    fn create_multisampled_color_texture(
+        device: &wgpu::Device,
+        size: wgpu::Extent3d,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Option<ColorTexture2DPackage> {
+        if sample_count == 1 {
+            return None;
+        }
+
+        let gpu_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("MULTISAMPLED_COLOR_TEXTURE"),
+            size,
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let gpu_texture_view = gpu_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Some(ColorTexture2DPackage {
+            gpu_texture,
+            gpu_texture_view,
+        })
+    }
+
+    fn create_depth_texture(
+        device: &wgpu::Device,
+        size: wgpu::Extent3d,
+        sample_count: u32,
+    ) -> DepthTexture2DPackage {
         let gpu_texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("DEPTH_TEXTURE"),
             size,
             mip_level_count: 1,
-            sample_count: 1,
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Depth32Float,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             view_formats: &[],
         });
         let gpu_texture_view = gpu_texture.create_view(&wgpu::TextureViewDescriptor::default());
@@ -7,6 +7,7 @@ use crate::render::node::RenderNode;
 use crate::render::pipeline::{RenderPipeline, RenderPipelineConfiguration};
 use crate::render::sampler::Sampler;
 use crate::render::shader::{ShaderModulePackage, ShaderTemplateConfiguration};
+use crate::render::skin::Skin;
 use crate::render::texture::Texture2DPackage;
 
 #[derive(Default)]
@@ -17,6 +18,7 @@ pub struct RenderSystemSceneStorage {
     pub texture_registry: HashMap<usize, std::rc::Rc<Texture2DPackage>>,
     pub image_registry: HashMap<usize, std::rc::Rc<Image>>,
     pub sampler_registry: HashMap<Option<usize>, std::rc::Rc<Sampler>>,
+    pub skin_registry: HashMap<usize, std::rc::Rc<Skin>>,
     pub render_pipeline_registry: HashMap<RenderPipelineConfiguration, std::rc::Rc<RenderPipeline>>,
     pub shader_module_package_registry:
         HashMap<ShaderTemplateConfiguration, std::rc::Rc<ShaderModulePackage>>,
@@ -0,0 +1,156 @@
+use anyhow::Result;
+
+use crate::error::Error;
+
+pub enum RenderTarget {
+    Surface {
+        surface: wgpu::Surface<'static>,
+        config: wgpu::SurfaceConfiguration,
+    },
+    Texture {
+        gpu_texture: wgpu::Texture,
+    },
+}
+
+impl RenderTarget {
+    pub fn from_dimensions(
+        device: &wgpu::Device,
+        view_dimensions: winit::dpi::PhysicalSize<u32>,
+    ) -> Self {
+        RenderTarget::Texture {
+            gpu_texture: RenderTarget::create_texture(device, view_dimensions),
+        }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match self {
+            RenderTarget::Surface { config, .. } => config.format,
+            RenderTarget::Texture { gpu_texture } => gpu_texture.format(),
+        }
+    }
+
+    pub fn set_view_dimensions(
+        &mut self,
+        device: &wgpu::Device,
+        view_dimensions: winit::dpi::PhysicalSize<u32>,
+    ) {
+        match self {
+            RenderTarget::Surface { surface, config } => {
+                config.width = view_dimensions.width;
+                config.height = view_dimensions.height;
+                surface.configure(device, config);
+            }
+            RenderTarget::Texture { gpu_texture } => {
+                *gpu_texture = RenderTarget::create_texture(device, view_dimensions);
+            }
+        }
+    }
+
+    pub fn get_current_surface_texture(&self) -> Result<wgpu::SurfaceTexture> {
+        match self {
+            RenderTarget::Surface { surface, .. } => Ok(surface.get_current_texture()?),
+            RenderTarget::Texture { .. } => Err(Error::new(String::from(
+                "The render target is an offscreen texture and has no surface.",
+            ))
+            .into()),
+        }
+    }
+
+    pub fn get_texture(&self) -> Result<&wgpu::Texture> {
+        match self {
+            RenderTarget::Surface { .. } => Err(Error::new(String::from(
+                "The render target is a surface and has no offscreen texture.",
+            ))
+            .into()),
+            RenderTarget::Texture { gpu_texture } => Ok(gpu_texture),
+        }
+    }
+
+    pub fn read_image(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        mut encoder: wgpu::CommandEncoder,
+    ) -> Result<image::RgbaImage> {
+        let gpu_texture = self.get_texture()?;
+        let (width, height) = (gpu_texture.width(), gpu_texture.height());
+
+        // Rows copied into a buffer must be aligned, so the padding is stripped after reading
+        let unpadded_bytes_per_row = 4 * width;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let gpu_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("RENDER_TARGET_READBACK_BUFFER"),
+            size: (padded_bytes_per_row * height) as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: gpu_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &gpu_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            gpu_texture.size(),
+        );
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let buffer_slice = gpu_buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+
+        let mut data = Vec::<u8>::with_capacity((unpadded_bytes_per_row * height) as usize);
+        {
+            let padded_data = buffer_slice.get_mapped_range();
+            for row in padded_data.chunks(padded_bytes_per_row as usize) {
+                data.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            }
+        }
+        gpu_buffer.unmap();
+
+        match image::RgbaImage::from_raw(width, height, data) {
+            Some(image) => Ok(image),
+            None => Err(Error::new(String::from(
+                "The data read from the render target does not match its dimensions.",
+            ))
+            .into()),
+        }
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        view_dimensions: winit::dpi::PhysicalSize<u32>,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("RENDER_TARGET_TEXTURE"),
+            size: wgpu::Extent3d {
+                width: view_dimensions.width.max(1),
+                height: view_dimensions.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+}
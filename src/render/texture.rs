@@ -7,6 +7,12 @@ pub struct Texture2DPackage {
     pub sampler: std::rc::Rc<Sampler>,
 }
 
+pub struct ColorTexture2DPackage {
+    #[allow(dead_code)]
+    pub gpu_texture: wgpu::Texture,
+    pub gpu_texture_view: wgpu::TextureView,
+}
+
 pub struct DepthTexture2DPackage {
     #[allow(dead_code)]
     pub gpu_texture: wgpu::Texture,
@@ -67,6 +67,16 @@ impl ViewEnvironment {
         &self.ibl_environment.skybox
     }
 
+    pub fn camera_position(&self) -> cgmath::Point3<f32> {
+        let translation = self.get_camera_transform().translation;
+
+        cgmath::Point3 {
+            x: translation.x,
+            y: translation.y,
+            z: translation.z,
+        }
+    }
+
     pub fn set_user_camera_transform(&mut self, transform: Transform) {
         let update_ibl_environment_view_projection = self.active_camera.is_none()
             && self.user_camera.transform.rotation != transform.rotation;
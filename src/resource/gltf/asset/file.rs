@@ -4,6 +4,9 @@ use anyhow::Result;
 
 use crate::resource::gltf::asset::GltfAsset;
 
+// Required extensions that are implemented by the viewer instead of the gltf crate
+const VIEWER_EXTENSIONS: &[&str] = &["KHR_mesh_quantization", "KHR_texture_transform"];
+
 pub struct FileSystemGltfAsset {
     gltf: gltf::Gltf,
     pub root: String,
@@ -46,7 +49,24 @@ impl FileSystemGltfAsset {
             }
         };
 
-        let parsed_gltf = gltf::Gltf::open(gltf_path)?;
+        let gltf = gltf::Gltf::from_reader_without_validation(std::io::BufReader::new(
+            std::fs::File::open(gltf_path)?,
+        ))?;
+
+        Self::from_gltf(gltf, root)
+    }
+
+    pub fn from_gltf(gltf: gltf::Gltf, root: String) -> Result<Self> {
+        let gltf::Gltf { document, blob } = gltf;
+
+        let mut json = document.into_json();
+        json.extensions_required
+            .retain(|extension| !VIEWER_EXTENSIONS.contains(&extension.as_str()));
+
+        let parsed_gltf = gltf::Gltf {
+            document: gltf::Document::from_json(json)?,
+            blob,
+        };
 
         Ok(Self {
             gltf: parsed_gltf,
@@ -1,7 +1,8 @@
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
+use base64::Engine;
 
 use crate::error::Error;
 use crate::resource::gltf::asset::file::FileSystemGltfAsset;
@@ -11,6 +12,7 @@ use crate::resource::gltf::loader::GltfLoader;
 pub struct FileSystemGltfLoader<'a> {
     asset: &'a FileSystemGltfAsset,
     buffer_registry: HashMap<usize, Vec<u8>>,
+    accessor_registry: HashMap<usize, Vec<u8>>,
     image_registry: HashMap<String, Vec<u8>>,
 }
 
@@ -19,19 +21,24 @@ impl<'a> FileSystemGltfLoader<'a> {
         Self {
             asset,
             buffer_registry: HashMap::new(),
+            accessor_registry: HashMap::new(),
             image_registry: HashMap::new(),
         }
     }
 
-    fn get_buffer_read_info(&self, accessor_id: usize) -> Result<GltfBufferReadInfo> {
-        let accessor = match self.asset.gltf().accessors().nth(accessor_id) {
-            Some(accessor) => accessor,
+    fn get_accessor(&self, accessor_id: usize) -> Result<gltf::Accessor<'a>> {
+        let asset = self.asset;
+
+        match asset.gltf().accessors().nth(accessor_id) {
+            Some(accessor) => Ok(accessor),
             None => {
-                return Err(
-                    Error::new(format!("The given accessor ID is invalid: {accessor_id}")).into(),
-                )
+                Err(Error::new(format!("The given accessor ID is invalid: {accessor_id}")).into())
             }
-        };
+        }
+    }
+
+    fn get_buffer_read_info(&self, accessor_id: usize) -> Result<GltfBufferReadInfo> {
+        let accessor = self.get_accessor(accessor_id)?;
 
         let view = match accessor.view() {
             Some(view) => view,
@@ -43,32 +50,52 @@ impl<'a> FileSystemGltfLoader<'a> {
             }
         };
 
-        let buffer = view.buffer();
+        // Elements of interleaved views are not contiguous and have to be copied out individually
+        let stride = match view.stride() {
+            Some(stride) if stride != accessor.size() => Some(stride),
+            _ => None,
+        };
 
         Ok(GltfBufferReadInfo {
-            index: buffer.index(),
-            uri: self.get_buffer_uri(&buffer)?,
+            index: view.buffer().index(),
             offset: view.offset() + accessor.offset(),
             length: accessor.count() * accessor.size(),
+            element_size: accessor.size(),
+            stride,
         })
     }
 
-    fn get_buffer_uri(&self, buffer: &gltf::Buffer) -> Result<String> {
-        match buffer.source() {
-            gltf::buffer::Source::Uri(uri) => Ok(String::from(uri)),
-            gltf::buffer::Source::Bin => {
-                Err(Error::new(String::from("Loading inline buffers is not supported.")).into())
+    fn requires_accessor_data(accessor: &gltf::Accessor) -> bool {
+        match accessor.view() {
+            Some(view) => {
+                accessor.sparse().is_some()
+                    || view
+                        .stride()
+                        .is_some_and(|stride| stride != accessor.size())
             }
+            None => true,
         }
     }
 
-    fn load_buffer_data(&mut self, buffer_id: usize, uri: &str) -> Result<()> {
+    fn get_buffer(&self, buffer_id: usize) -> Result<gltf::Buffer<'_>> {
+        match self.asset.gltf().buffers().nth(buffer_id) {
+            Some(buffer) => Ok(buffer),
+            None => Err(Error::new(format!("The given buffer ID is invalid: {buffer_id}")).into()),
+        }
+    }
+
+    fn load_buffer_data(&mut self, buffer_id: usize) -> Result<()> {
         if self.buffer_registry.contains_key(&buffer_id) {
             return Ok(());
         }
 
-        let buffer_path = Path::new(&self.asset.root).join(uri);
-        let data = std::fs::read(buffer_path)?;
+        let uri = match self.get_buffer(buffer_id)?.source() {
+            gltf::buffer::Source::Uri(uri) => uri,
+            // The binary chunk of a .glb file is already held in memory by the asset
+            gltf::buffer::Source::Bin => return Ok(()),
+        };
+
+        let data = self.read_uri_data(uri)?;
 
         self.buffer_registry.insert(buffer_id, data);
 
@@ -76,17 +103,188 @@ impl<'a> FileSystemGltfLoader<'a> {
     }
 
     fn read_buffer_data(&self, buffer_id: usize, offset: usize, length: usize) -> Result<&[u8]> {
-        let data = match self.buffer_registry.get(&buffer_id) {
-            Some(data) => data,
-            None => {
+        let data = match self.get_buffer(buffer_id)?.source() {
+            gltf::buffer::Source::Uri(_) => match self.buffer_registry.get(&buffer_id) {
+                Some(data) => data,
+                None => {
+                    return Err(Error::new(format!(
+                        "The given buffer ID is not associated with a loaded buffer: {buffer_id}"
+                    ))
+                    .into())
+                }
+            },
+            gltf::buffer::Source::Bin => match &self.asset.gltf().blob {
+                Some(blob) => blob,
+                None => {
+                    return Err(Error::new(format!(
+                        "The given buffer ID refers to a binary chunk that is not present in the asset: {buffer_id}"
+                    ))
+                    .into())
+                }
+            },
+        };
+
+        // Offsets and lengths are not validated against the size of the buffer by the gltf crate
+        match offset
+            .checked_add(length)
+            .and_then(|end| data.get(offset..end))
+        {
+            Some(data) => Ok(data),
+            None => Err(Error::new(format!(
+                "The range of {length} bytes at offset {offset} exceeds buffer {buffer_id}, which holds {} bytes.",
+                data.len()
+            ))
+            .into()),
+        }
+    }
+
+    fn load_accessor_data(&mut self, accessor: &gltf::Accessor) -> Result<()> {
+        if self.accessor_registry.contains_key(&accessor.index()) {
+            return Ok(());
+        }
+
+        let mut data = match accessor.view() {
+            Some(_) => {
+                let buffer_read_info = self.get_buffer_read_info(accessor.index())?;
+                self.load_buffer_data(buffer_read_info.index)?;
+
+                match buffer_read_info.stride {
+                    Some(stride) => self.read_strided_buffer_data(&buffer_read_info, stride)?,
+                    None => self
+                        .read_buffer_data(
+                            buffer_read_info.index,
+                            buffer_read_info.offset,
+                            buffer_read_info.length,
+                        )?
+                        .to_vec(),
+                }
+            }
+            // Accessors without a buffer view are initialized with zeros
+            None => vec![0; accessor.count() * accessor.size()],
+        };
+
+        if let Some(sparse) = accessor.sparse() {
+            let indices = sparse.indices();
+            let values = sparse.values();
+
+            self.load_buffer_data(indices.view().buffer().index())?;
+            self.load_buffer_data(values.view().buffer().index())?;
+
+            let index_size = FileSystemGltfLoader::get_sparse_index_size(&indices.index_type());
+
+            let indices_data = self.read_buffer_data(
+                indices.view().buffer().index(),
+                indices.view().offset() + indices.offset(),
+                sparse.count() * index_size,
+            )?;
+            let values_data = self.read_buffer_data(
+                values.view().buffer().index(),
+                values.view().offset() + values.offset(),
+                sparse.count() * accessor.size(),
+            )?;
+
+            FileSystemGltfLoader::apply_sparse_values(
+                &mut data,
+                accessor.size(),
+                indices.index_type(),
+                indices_data,
+                values_data,
+            )?;
+        }
+
+        self.accessor_registry.insert(accessor.index(), data);
+
+        Ok(())
+    }
+
+    fn read_strided_buffer_data(
+        &self,
+        buffer_read_info: &GltfBufferReadInfo,
+        stride: usize,
+    ) -> Result<Vec<u8>> {
+        let count = buffer_read_info.length / buffer_read_info.element_size;
+        let strided_length = match count {
+            0 => 0,
+            _ => (count - 1) * stride + buffer_read_info.element_size,
+        };
+
+        let strided_data = self.read_buffer_data(
+            buffer_read_info.index,
+            buffer_read_info.offset,
+            strided_length,
+        )?;
+
+        Ok(FileSystemGltfLoader::deinterleave(
+            strided_data,
+            buffer_read_info.element_size,
+            stride,
+        ))
+    }
+
+    fn deinterleave(strided_data: &[u8], element_size: usize, stride: usize) -> Vec<u8> {
+        let mut data = Vec::<u8>::with_capacity(strided_data.len().div_ceil(stride) * element_size);
+        for element in strided_data.chunks(stride) {
+            data.extend_from_slice(&element[..element_size]);
+        }
+
+        data
+    }
+
+    fn get_sparse_index_size(index_type: &gltf::accessor::sparse::IndexType) -> usize {
+        match index_type {
+            gltf::accessor::sparse::IndexType::U8 => 1,
+            gltf::accessor::sparse::IndexType::U16 => 2,
+            gltf::accessor::sparse::IndexType::U32 => 4,
+        }
+    }
+
+    fn apply_sparse_values(
+        data: &mut [u8],
+        element_size: usize,
+        index_type: gltf::accessor::sparse::IndexType,
+        indices: &[u8],
+        values: &[u8],
+    ) -> Result<()> {
+        let index_size = FileSystemGltfLoader::get_sparse_index_size(&index_type);
+
+        for (index, value) in indices
+            .chunks_exact(index_size)
+            .zip(values.chunks_exact(element_size))
+        {
+            let index = match index_type {
+                gltf::accessor::sparse::IndexType::U8 => index[0] as usize,
+                gltf::accessor::sparse::IndexType::U16 => {
+                    u16::from_le_bytes([index[0], index[1]]) as usize
+                }
+                gltf::accessor::sparse::IndexType::U32 => {
+                    u32::from_le_bytes([index[0], index[1], index[2], index[3]]) as usize
+                }
+            };
+
+            let begin = index * element_size;
+            let end = begin + element_size;
+
+            if end > data.len() {
                 return Err(Error::new(format!(
-                    "The given buffer ID is not associated with a loaded buffer: {buffer_id}"
+                    "The sparse accessor references an element that is out of range: {index}"
                 ))
-                .into())
+                .into());
             }
-        };
 
-        Ok(&data[offset..offset + length])
+            data[begin..end].copy_from_slice(value);
+        }
+
+        Ok(())
+    }
+
+    fn read_accessor_data(&self, accessor_id: usize) -> Result<&[u8]> {
+        match self.accessor_registry.get(&accessor_id) {
+            Some(data) => Ok(&data[..]),
+            None => Err(Error::new(format!(
+                "The given accessor ID is not associated with loaded accessor data: {accessor_id}"
+            ))
+            .into()),
+        }
     }
 
     fn load_image_data(&mut self, uri: &String) -> Result<()> {
@@ -94,14 +292,47 @@ impl<'a> FileSystemGltfLoader<'a> {
             return Ok(());
         }
 
-        let image_path = Path::new(&self.asset.root).join(uri);
-        let data = std::fs::read(image_path)?;
+        let data = self.read_uri_data(uri)?;
 
         self.image_registry.insert(uri.to_string(), data);
 
         Ok(())
     }
 
+    fn read_uri_data(&self, uri: &str) -> Result<Vec<u8>> {
+        if let Some(data_uri) = uri.strip_prefix("data:") {
+            return FileSystemGltfLoader::decode_data_uri(data_uri);
+        }
+
+        Ok(std::fs::read(FileSystemGltfLoader::get_uri_path(
+            &self.asset.root,
+            uri,
+        )?)?)
+    }
+
+    fn get_uri_path(root: &str, uri: &str) -> Result<PathBuf> {
+        let path = urlencoding::decode(uri)?;
+
+        Ok(Path::new(root).join(path.as_ref()))
+    }
+
+    fn decode_data_uri(data_uri: &str) -> Result<Vec<u8>> {
+        let (media_type, data) = match data_uri.split_once(',') {
+            Some(parts) => parts,
+            None => {
+                return Err(Error::new(String::from(
+                    "The given data URI does not contain a data section.",
+                ))
+                .into())
+            }
+        };
+
+        match media_type.ends_with(";base64") {
+            true => Ok(base64::engine::general_purpose::STANDARD.decode(data)?),
+            false => Ok(urlencoding::decode_binary(data.as_bytes()).into_owned()),
+        }
+    }
+
     fn read_image_data<'b>(&'b self, uri: &String) -> Result<&'b [u8]> {
         let data = match self.image_registry.get(uri) {
             Some(data) => data,
@@ -123,17 +354,26 @@ impl<'a> GltfLoader for FileSystemGltfLoader<'a> {
     }
 
     fn load_bytes_from_accessor(&mut self, accessor_id: usize) -> Result<&[u8]> {
-        let buffer_read_info = self.get_buffer_read_info(accessor_id)?;
-        self.load_buffer_data(buffer_read_info.index, &buffer_read_info.uri)?;
+        let accessor = self.get_accessor(accessor_id)?;
 
-        self.read_buffer_data(
-            buffer_read_info.index,
-            buffer_read_info.offset,
-            buffer_read_info.length,
-        )
+        match FileSystemGltfLoader::requires_accessor_data(&accessor) {
+            true => self.load_accessor_data(&accessor)?,
+            false => {
+                let buffer_read_info = self.get_buffer_read_info(accessor_id)?;
+                self.load_buffer_data(buffer_read_info.index)?;
+            }
+        }
+
+        self.read_bytes_from_accessor(accessor_id)
     }
 
     fn read_bytes_from_accessor(&self, accessor_id: usize) -> Result<&[u8]> {
+        let accessor = self.get_accessor(accessor_id)?;
+
+        if FileSystemGltfLoader::requires_accessor_data(&accessor) {
+            return self.read_accessor_data(accessor_id);
+        }
+
         let buffer_read_info = self.get_buffer_read_info(accessor_id)?;
 
         self.read_buffer_data(
@@ -158,14 +398,11 @@ impl<'a> GltfLoader for FileSystemGltfLoader<'a> {
                 (self.read_image_data(&uri)?, mime_type)
             }
             gltf::image::Source::View { view, mime_type } => {
-                let buffer = view.buffer();
-
-                let index = buffer.index();
-                let uri = self.get_buffer_uri(&buffer)?;
+                let index = view.buffer().index();
                 let offset = view.offset();
                 let length = view.length();
 
-                self.load_buffer_data(index, &uri)?;
+                self.load_buffer_data(index)?;
                 (
                     self.read_buffer_data(index, offset, length)?,
                     Some(mime_type),
@@ -197,7 +434,231 @@ impl<'a> GltfLoader for FileSystemGltfLoader<'a> {
 
 struct GltfBufferReadInfo {
     index: usize,
-    uri: String,
     offset: usize,
     length: usize,
+    element_size: usize,
+    stride: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use gltf::accessor::sparse::IndexType;
+
+    use super::FileSystemGltfLoader;
+    use crate::resource::gltf::asset::file::FileSystemGltfAsset;
+    use crate::resource::gltf::asset::GltfAsset;
+    use crate::resource::gltf::loader::GltfLoader;
+
+    fn vec2_bytes(elements: &[[f32; 2]]) -> Vec<u8> {
+        bytemuck::cast_slice(elements).to_vec()
+    }
+
+    fn asset_from_slice(data: &[u8]) -> FileSystemGltfAsset {
+        let gltf = gltf::Gltf::from_slice_without_validation(data).unwrap();
+        FileSystemGltfAsset::from_gltf(gltf, String::from(".")).unwrap()
+    }
+
+    #[test]
+    fn deinterleave_copies_elements_out_of_strided_data() {
+        // The last element is not followed by the padding of the other interleaved attribute
+        let strided_data = [1, 2, 0xAA, 0xAA, 3, 4, 0xAA, 0xAA, 5, 6];
+
+        assert_eq!(
+            FileSystemGltfLoader::deinterleave(&strided_data, 2, 4),
+            vec![1, 2, 3, 4, 5, 6],
+        );
+    }
+
+    #[test]
+    fn decode_data_uri_decodes_base64_data() {
+        assert_eq!(
+            FileSystemGltfLoader::decode_data_uri("application/octet-stream;base64,AAEC/w==")
+                .unwrap(),
+            vec![0, 1, 2, 255],
+        );
+    }
+
+    #[test]
+    fn decode_data_uri_decodes_percent_encoded_data() {
+        assert_eq!(
+            FileSystemGltfLoader::decode_data_uri("text/plain,a%20b%00%FF").unwrap(),
+            vec![b'a', b' ', b'b', 0, 255],
+        );
+    }
+
+    #[test]
+    fn decode_data_uri_rejects_uris_without_data_section() {
+        assert!(FileSystemGltfLoader::decode_data_uri("application/octet-stream;base64").is_err());
+    }
+
+    #[test]
+    fn get_uri_path_decodes_percent_encoded_paths() {
+        assert_eq!(
+            FileSystemGltfLoader::get_uri_path("assets", "textures/base%20color%23.png").unwrap(),
+            std::path::Path::new("assets/textures/base color#.png"),
+        );
+    }
+
+    #[test]
+    fn apply_sparse_values_fills_zero_initialized_data() {
+        let mut data = vec![0; 3 * 8];
+        let indices = [2_u8, 0];
+        let values = vec2_bytes(&[[1.0, 2.0], [3.0, 4.0]]);
+
+        FileSystemGltfLoader::apply_sparse_values(&mut data, 8, IndexType::U8, &indices, &values)
+            .unwrap();
+
+        assert_eq!(data, vec2_bytes(&[[3.0, 4.0], [0.0, 0.0], [1.0, 2.0]]),);
+    }
+
+    #[test]
+    fn apply_sparse_values_replaces_elements_of_base_data() {
+        let mut data = vec2_bytes(&[[1.0, 1.0], [2.0, 2.0], [3.0, 3.0], [4.0, 4.0]]);
+        let indices: Vec<u8> = bytemuck::cast_slice(&[1_u16, 3]).to_vec();
+        let values = vec2_bytes(&[[-2.0, -2.0], [-4.0, -4.0]]);
+
+        FileSystemGltfLoader::apply_sparse_values(&mut data, 8, IndexType::U16, &indices, &values)
+            .unwrap();
+
+        assert_eq!(
+            data,
+            vec2_bytes(&[[1.0, 1.0], [-2.0, -2.0], [3.0, 3.0], [-4.0, -4.0]]),
+        );
+    }
+
+    #[test]
+    fn apply_sparse_values_reads_u32_indices() {
+        let mut data = vec![0; 4];
+        let indices: Vec<u8> = bytemuck::cast_slice(&[3_u32]).to_vec();
+
+        FileSystemGltfLoader::apply_sparse_values(&mut data, 1, IndexType::U32, &indices, &[7])
+            .unwrap();
+
+        assert_eq!(data, vec![0, 0, 0, 7]);
+    }
+
+    fn glb_bytes(json: &[u8], bin: &[u8]) -> Vec<u8> {
+        let mut json = json.to_vec();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let length = 12 + 8 + json.len() + 8 + bin.len();
+
+        let mut data = Vec::with_capacity(length);
+        data.extend_from_slice(b"glTF");
+        data.extend_from_slice(&2_u32.to_le_bytes());
+        data.extend_from_slice(&(length as u32).to_le_bytes());
+        data.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        data.extend_from_slice(b"JSON");
+        data.extend_from_slice(&json);
+        data.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        data.extend_from_slice(b"BIN\0");
+        data.extend_from_slice(bin);
+
+        data
+    }
+
+    #[test]
+    fn load_from_binary_chunk_reads_accessors_and_images() {
+        let mut image_data = Vec::new();
+        image::RgbaImage::from_pixel(1, 1, image::Rgba([10, 20, 30, 255]))
+            .write_to(
+                &mut std::io::Cursor::new(&mut image_data),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+
+        // The image follows the accessor data within the binary chunk
+        let mut bin = bytemuck::cast_slice(&[1.0_f32, 2.0]).to_vec();
+        bin.extend_from_slice(&image_data);
+        bin.resize(bin.len().next_multiple_of(4), 0);
+
+        let asset = asset_from_slice(&glb_bytes(
+            format!(
+                r#"{{
+                    "asset": {{ "version": "2.0" }},
+                    "buffers": [{{ "byteLength": {} }}],
+                    "bufferViews": [
+                        {{ "buffer": 0, "byteOffset": 0, "byteLength": 8 }},
+                        {{ "buffer": 0, "byteOffset": 8, "byteLength": {} }}
+                    ],
+                    "accessors": [{{ "bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR" }}],
+                    "images": [{{ "bufferView": 1, "mimeType": "image/png" }}]
+                }}"#,
+                bin.len(),
+                image_data.len(),
+            )
+            .as_bytes(),
+            &bin,
+        ));
+        let mut loader = FileSystemGltfLoader::new(&asset);
+
+        assert_eq!(
+            loader.load_bytes_from_accessor(0).unwrap(),
+            bytemuck::cast_slice::<f32, u8>(&[1.0, 2.0]),
+        );
+        assert_eq!(
+            loader.load_image(0).unwrap().get_pixel(0, 0),
+            &image::Rgba([10, 20, 30, 255]),
+        );
+    }
+
+    #[test]
+    fn load_bytes_from_accessor_rejects_truncated_binary_chunks() {
+        // The buffer declares 8 bytes, while the binary chunk only holds 4 of them
+        let asset = asset_from_slice(&glb_bytes(
+            br#"{
+                "asset": { "version": "2.0" },
+                "buffers": [{ "byteLength": 8 }],
+                "bufferViews": [{ "buffer": 0, "byteOffset": 0, "byteLength": 8 }],
+                "accessors": [{ "bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR" }]
+            }"#,
+            &[0; 4],
+        ));
+        let mut loader = FileSystemGltfLoader::new(&asset);
+
+        assert!(loader.load_bytes_from_accessor(0).is_err());
+    }
+
+    #[test]
+    fn load_accessor_data_rejects_out_of_range_sparse_values() {
+        // The sparse values of the second element would be read past the end of the 8-byte buffer
+        let asset = asset_from_slice(
+            br#"{
+                "asset": { "version": "2.0" },
+                "buffers": [{ "byteLength": 8, "uri": "data:application/octet-stream;base64,AAAAAAAAAAA=" }],
+                "bufferViews": [
+                    { "buffer": 0, "byteOffset": 0, "byteLength": 2 },
+                    { "buffer": 0, "byteOffset": 4, "byteLength": 4 }
+                ],
+                "accessors": [{
+                    "componentType": 5126,
+                    "count": 2,
+                    "type": "SCALAR",
+                    "sparse": {
+                        "count": 2,
+                        "indices": { "bufferView": 0, "componentType": 5121 },
+                        "values": { "bufferView": 1 }
+                    }
+                }]
+            }"#,
+        );
+        let accessor = asset.gltf().accessors().next().unwrap();
+        let mut loader = FileSystemGltfLoader::new(&asset);
+
+        assert!(loader.load_accessor_data(&accessor).is_err());
+    }
+
+    #[test]
+    fn apply_sparse_values_rejects_out_of_range_indices() {
+        let mut data = vec![0; 2 * 8];
+        let values = vec2_bytes(&[[1.0, 2.0]]);
+
+        assert!(FileSystemGltfLoader::apply_sparse_values(
+            &mut data,
+            8,
+            IndexType::U8,
+            &[2],
+            &values
+        )
+        .is_err());
+    }
 }
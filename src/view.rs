@@ -11,10 +11,10 @@ pub struct ViewSystem {
 }
 
 impl ViewSystem {
-    pub async fn from_window(window: winit::window::Window) -> Result<Self> {
+    pub async fn from_window(window: winit::window::Window, sample_count: u32) -> Result<Self> {
         let window = std::sync::Arc::new(window);
 
-        let mut render_system = RenderSystem::from_window(window.clone()).await?;
+        let mut render_system = RenderSystem::from_window(window.clone(), sample_count).await?;
 
         let camera_controller = OrbitalCameraController::new(
             (0.0, 0.0, 0.0).into(),